@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::class::Class;
+use crate::element::Element;
+use crate::kana::reading_match;
+use crate::matching::normalize;
+use crate::matchup::Matchup;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    pub alias: Vec<String>,
+    pub advantages: Vec<Matchup>,
+    pub disadvantages: Vec<Matchup>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<Class>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element: Option<Element>,
+}
+
+impl Character {
+    /// Returns true if `name_or_alias` is this character's name or one of its aliases,
+    /// ignoring case, character width and extra whitespace, or if it reads the same
+    /// as the name in kana or romaji.
+    pub fn matches(&self, name_or_alias: &str) -> bool {
+        let needle = normalize(name_or_alias);
+        self.names().any(|name| normalize(name) == needle) || self.matches_reading(&needle).is_some()
+    }
+
+    /// Returns how many kana of this character's name `input` matched when read
+    /// as kana or romaji, or `None` if it does not match (see [`reading_match`]).
+    pub fn matches_reading(&self, input: &str) -> Option<usize> {
+        reading_match(input, &self.name)
+    }
+
+    /// Returns true if `name_or_alias` is exactly this character's name or one of its aliases.
+    pub fn matches_exactly(&self, name_or_alias: &str) -> bool {
+        self.names().any(|name| name == name_or_alias)
+    }
+
+    /// The character's name followed by its aliases.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.alias.iter().map(String::as_str))
+    }
+}
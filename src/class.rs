@@ -0,0 +1,58 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Character class (role) that matchups may refer to instead of a specific character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Class {
+    Warrior,
+    Knight,
+    Assassin,
+    Archer,
+    Mage,
+    Healer,
+    Berserker,
+}
+
+impl Class {
+    pub const ALL: [Class; 7] = [
+        Class::Warrior,
+        Class::Knight,
+        Class::Assassin,
+        Class::Archer,
+        Class::Mage,
+        Class::Healer,
+        Class::Berserker,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Class::Warrior => "Warrior",
+            Class::Knight => "Knight",
+            Class::Assassin => "Assassin",
+            Class::Archer => "Archer",
+            Class::Mage => "Mage",
+            Class::Healer => "Healer",
+            Class::Berserker => "Berserker",
+        }
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Class {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Class::ALL
+            .iter()
+            .copied()
+            .find(|class| class.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown class: {}", s))
+    }
+}
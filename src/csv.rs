@@ -0,0 +1,302 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::character::Character;
+use crate::error::{Error, Result};
+use crate::matchup::{Matchup, MatchupRef, DEFAULT_WEIGHT};
+use crate::output::CHARACTER_HEADER;
+use crate::store::read_source;
+
+/// A CSV row that could not be turned into a character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// Line the row starts on, counting the header as line 1.
+    pub row: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.message)
+    }
+}
+
+/// The characters read from a CSV table, each with the row it came from, and
+/// the rows that failed to parse.
+#[derive(Debug, Clone, Default)]
+pub struct CsvImport {
+    pub characters: Vec<(usize, Character)>,
+    pub errors: Vec<RowError>,
+}
+
+impl CsvImport {
+    /// The row `name` was read from.
+    pub fn row_of(&self, name: &str) -> Option<usize> {
+        self.characters.iter().find(|(_, c)| c.name == name).map(|(row, _)| *row)
+    }
+}
+
+pub fn load_csv<P: AsRef<Path>>(file_path: P) -> Result<CsvImport> {
+    let source = read_source(file_path.as_ref())?;
+    parse_csv(&source).map_err(|err| err.in_file(file_path))
+}
+
+/// Parses a table in the layout `--output csv` writes: a header row naming the
+/// columns (`name` is required, unknown columns are ignored) and lists joined
+/// with `;`, with matchups as `NAME` or `NAME:WEIGHT`. In list entries `\`
+/// makes the next character literal, e.g. `\;` or `\:`.
+///
+/// Fails only if the table itself is malformed; bad rows end up in
+/// [`CsvImport::errors`].
+pub fn parse_csv(source: &str) -> Result<CsvImport> {
+    let mut records = records(source.trim_start_matches('\u{feff}'))?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Err(Error::Parse { file: None, line: 1, column: 1, message: "missing header row".to_string() });
+    };
+    let mut columns: Vec<Option<usize>> = Vec::new();
+    for (i, cell) in header.iter().enumerate() {
+        let name = cell.trim().to_lowercase();
+        let column = CHARACTER_HEADER.iter().position(|&known| known == name);
+        if column.is_some() && columns.contains(&column) {
+            return Err(Error::Parse { file: None, line: 1, column: i + 1, message: format!("duplicate column: {}", name) });
+        }
+        columns.push(column);
+    }
+    if !columns.contains(&Some(0)) {
+        return Err(Error::Parse { file: None, line: 1, column: 1, message: "missing name column".to_string() });
+    }
+
+    let mut import = CsvImport::default();
+    for (row, fields) in records {
+        if fields.len() > columns.len() {
+            let message = format!("expected at most {} fields, found {}", columns.len(), fields.len());
+            import.errors.push(RowError { row, message });
+            continue;
+        }
+        let mut cells = [""; CHARACTER_HEADER.len()];
+        for (column, field) in columns.iter().zip(&fields) {
+            if let Some(column) = column {
+                cells[*column] = field.trim();
+            }
+        }
+        match character(&cells) {
+            Ok(character) => import.characters.push((row, character)),
+            Err(message) => import.errors.push(RowError { row, message }),
+        }
+    }
+    Ok(import)
+}
+
+/// Builds a character from cells in [`CHARACTER_HEADER`] order.
+fn character(cells: &[&str; CHARACTER_HEADER.len()]) -> std::result::Result<Character, String> {
+    let [name, alias, class, element, advantages, disadvantages] = *cells;
+    if name.is_empty() {
+        return Err("missing name".to_string());
+    }
+    let matchups = |cell: &str| -> std::result::Result<Vec<Matchup>, String> {
+        list_items(cell).iter().map(|item| matchup(item)).collect()
+    };
+    Ok(Character {
+        name: name.to_string(),
+        alias: list_items(alias).iter().map(|item| text(item)).collect(),
+        advantages: matchups(advantages)?,
+        disadvantages: matchups(disadvantages)?,
+        class: Some(class).filter(|s| !s.is_empty()).map(str::parse).transpose()?,
+        element: Some(element).filter(|s| !s.is_empty()).map(str::parse).transpose()?,
+    })
+}
+
+/// A list entry, with whether each character was escaped with `\`.
+type Item = Vec<(char, bool)>;
+
+/// Splits a list cell on unescaped `;` and trims unescaped whitespace from each
+/// entry. Empty entries are dropped; an empty name is written as `""`.
+fn list_items(cell: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut item = Item::new();
+    let mut chars = cell.chars();
+    loop {
+        let next = chars.next();
+        match next {
+            Some('\\') => item.extend(chars.next().map(|c| (c, true))),
+            Some(c) if c != ';' => item.push((c, false)),
+            _ => {
+                match trim(&item) {
+                    [] => {}
+                    [('"', false), ('"', false)] => items.push(Item::new()),
+                    trimmed => items.push(trimmed.to_vec()),
+                }
+                if next.is_none() {
+                    return items;
+                }
+                item.clear();
+            }
+        }
+    }
+}
+
+fn trim(item: &[(char, bool)]) -> &[(char, bool)] {
+    let is_space = |&(c, escaped): &(char, bool)| !escaped && c.is_whitespace();
+    let start = item.iter().position(|c| !is_space(c)).unwrap_or(item.len());
+    let end = item.iter().rposition(|c| !is_space(c)).map_or(start, |i| i + 1);
+    &item[start..end]
+}
+
+fn text(item: &[(char, bool)]) -> String {
+    item.iter().map(|&(c, _)| c).collect()
+}
+
+/// Parses a matchup entry as `NAME` or `NAME:WEIGHT`, like [`Matchup::from_str`]
+/// but ignoring escaped colons.
+fn matchup(item: &[(char, bool)]) -> std::result::Result<Matchup, String> {
+    if let Some(colon) = item.iter().rposition(|&c| c == (':', false)) {
+        let weight = text(trim(&item[colon + 1..]));
+        if !weight.is_empty() && weight.chars().all(|c| c.is_ascii_digit()) {
+            let weight = weight.parse().map_err(|_| format!("invalid weight in \"{}\"", text(item)))?;
+            return Ok(Matchup::new(MatchupRef::Unresolved(text(trim(&item[..colon]))), weight));
+        }
+    }
+    Ok(Matchup::new(MatchupRef::Unresolved(text(item)), DEFAULT_WEIGHT))
+}
+
+/// Splits CSV text into records, each with the line it starts on. Quoted
+/// fields may contain commas, doubled quotes and line breaks; blank lines are
+/// skipped.
+fn records(source: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = source.chars().peekable();
+    let (mut line, mut start) = (1, 1);
+    let mut quoted_at = None;
+
+    while let Some(c) = chars.next() {
+        match (quoted_at.is_some(), c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted_at = None,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.trim().is_empty() => {
+                field.clear();
+                quoted_at = Some(line);
+            }
+            (false, ',') => fields.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                fields.push(std::mem::take(&mut field));
+                if fields.iter().any(|f| !f.is_empty()) {
+                    records.push((start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if let Some(line) = quoted_at {
+        return Err(Error::Parse { file: None, line, column: 1, message: "unterminated quoted field".to_string() });
+    }
+    fields.push(field);
+    if fields.iter().any(|f| !f.is_empty()) {
+        records.push((start, fields));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render_characters, OutputFormat};
+
+    fn characters(json: &str) -> Vec<Character> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn splits_quoted_fields_and_line_endings() {
+        let source = "a,\"b, c\",\"say \"\"hi\"\"\"\r\n\r\n\"multi\nline\",x , \n\nlast";
+        let expected = vec![
+            (1, vec!["a".to_string(), "b, c".to_string(), "say \"hi\"".to_string()]),
+            (3, vec!["multi\nline".to_string(), "x ".to_string(), " ".to_string()]),
+            (6, vec!["last".to_string()]),
+        ];
+        assert_eq!(records(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        match records("a,b\n\"open,c\nd") {
+            Err(Error::Parse { line: 2, .. }) => {}
+            other => panic!("expected a parse error on line 2, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_rows_by_header() {
+        let source = "\u{feff}Notes,NAME,Advantages,alias\nignored,Violet,\"Mage; ヴィオレタ:3\",V; Vio\n";
+        let import = parse_csv(source).unwrap();
+        assert!(import.errors.is_empty());
+        assert_eq!(
+            import.characters,
+            vec![(
+                2,
+                characters(r#"[{"name":"Violet","alias":["V","Vio"],"advantages":["Mage",{"name":"ヴィオレタ","weight":3}],"disadvantages":[]}]"#)
+                    .remove(0)
+            )]
+        );
+        assert_eq!(import.row_of("Violet"), Some(2));
+    }
+
+    #[test]
+    fn reports_bad_rows_and_keeps_the_rest() {
+        let source = "name,class,advantages\nA,Mage,\n,Mage,\nB,Pirate,\nC,,X:99999999999\nD,,,extra\nE,,\n";
+        let import = parse_csv(source).unwrap();
+        let names: Vec<&str> = import.characters.iter().map(|(_, c)| c.name.as_str()).collect();
+        assert_eq!(names, ["A", "E"]);
+        let rows: Vec<usize> = import.errors.iter().map(|e| e.row).collect();
+        assert_eq!(rows, [3, 4, 5, 6]);
+        assert_eq!(import.errors[0].to_string(), "row 3: missing name");
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        for source in ["", "alias,class\nA,Mage\n", "name,Name\nA,B\n"] {
+            assert!(matches!(parse_csv(source), Err(Error::Parse { line: 1, .. })), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn reads_escaped_list_entries() {
+        let source = "name,alias,advantages\nA,\"a\\;b; \\ x ;\"\"\"\"; ; \\\"\"\"\"\",\"B\\:12; C : 3\"\n";
+        let import = parse_csv(source).unwrap();
+        let character = &import.characters[0].1;
+        assert_eq!(character.alias, ["a;b", " x", "", "\"\""]);
+        let advantages: Vec<(&str, u32)> = character.advantages.iter().map(|m| (m.target.raw(), m.weight)).collect();
+        assert_eq!(advantages, [("B:12", 1), ("C", 3)]);
+    }
+
+    #[test]
+    fn round_trips_the_exported_table() {
+        let original = characters(
+            r#"[
+                {"name":"深淵のユピネ","alias":["Abyssal Yufine","A.Yufine"],"advantages":["Mage","Archer"],"disadvantages":[{"name":"Assassin","weight":2}],"class":"Mage","element":"Ice"},
+                {"name":"Quote \"Q\", Esq.","alias":[],"advantages":[],"disadvantages":["深淵のユピネ"]},
+                {"name":"Edge","alias":["a;b"," padded ","back\\slash","","\"\""],"advantages":["","Mode:2",{"name":"x: 3","weight":4}],"disadvantages":["; ",":"]}
+            ]"#,
+        );
+        let refs: Vec<&Character> = original.iter().collect();
+        let table = render_characters(&refs, OutputFormat::Csv).unwrap();
+        let import = parse_csv(&table).unwrap();
+        assert!(import.errors.is_empty(), "{:?}", import.errors);
+        let imported: Vec<Character> = import.characters.into_iter().map(|(_, c)| c).collect();
+        assert_eq!(imported, original);
+    }
+}
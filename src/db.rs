@@ -0,0 +1,375 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::character::Character;
+use crate::class::Class;
+use crate::element::AffinityTable;
+use crate::error::{Error, Result};
+use crate::format::DataFormat;
+use crate::kana::reading_key;
+use crate::matching::{normalize, suggestion_distance};
+use crate::matchup::{Matchup, MatchupRef};
+use crate::merge::{load_merged, Precedence};
+use crate::schema::{migrate, schema_version, Document, SCHEMA_VERSION};
+use crate::store::{format_of, read_source};
+
+/// Loads characters from a JSON, YAML or TOML file, by extension (see
+/// [`DataFormat::from_path`]), or merges the files in a directory with
+/// [`load_merged`]. [`STDIN`](crate::STDIN) reads standard input in the
+/// format [`DataFormat::detect`] finds.
+pub fn load_characters<P: AsRef<Path>>(file_path: P) -> Result<Vec<Character>> {
+    let path = file_path.as_ref();
+    if path.is_dir() {
+        return Ok(load_merged(&[path], Precedence::default())?.characters);
+    }
+    Ok(load_versioned(path)?.0)
+}
+
+/// Loads a data file along with the schema version it is written in.
+pub(crate) fn load_versioned(path: &Path) -> Result<(Vec<Character>, u32)> {
+    let source = read_source(path)?;
+    parse_versioned(&source, format_of(path, &source)).map_err(|err| err.in_file(path))
+}
+
+pub fn load_characters_as<P: AsRef<Path>>(file_path: P, format: DataFormat) -> Result<Vec<Character>> {
+    let source = read_source(file_path.as_ref())?;
+    parse_characters_as(&source, format).map_err(|err| err.in_file(file_path))
+}
+
+/// Parses JSON character data, see [`parse_characters_as`].
+pub fn parse_characters(source: &str) -> Result<Vec<Character>> {
+    parse_characters_as(source, DataFormat::Json)
+}
+
+/// Parses characters in `format`, in any supported layout (see
+/// [`SCHEMA_VERSION`](crate::SCHEMA_VERSION)): a versioned document, a list of
+/// characters, or a single character as in one-file-per-character
+/// directories.
+pub fn parse_characters_as(source: &str, format: DataFormat) -> Result<Vec<Character>> {
+    Ok(parse_versioned(source, format)?.0)
+}
+
+/// Parses characters along with the schema version of their layout.
+pub(crate) fn parse_versioned(source: &str, format: DataFormat) -> Result<(Vec<Character>, u32)> {
+    // JSON in the latest layout or a bare list is deserialized from the source,
+    // so that errors keep their position
+    if format == DataFormat::Json && source.trim_start().starts_with('[') {
+        return Ok((serde_json::from_str(source)?, 1));
+    }
+    let value = format.parse(source)?;
+    let version = schema_version(&value)?;
+    if format == DataFormat::Json && version == SCHEMA_VERSION {
+        return Ok((serde_json::from_str::<Document>(source)?.characters.into_owned(), version));
+    }
+    let document: Document = serde_json::from_value(migrate(value)?)?;
+    Ok((document.characters.into_owned(), version))
+}
+
+/// A meta search result: something the selected characters are weak to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Threat {
+    pub matchup: MatchupRef,
+    /// Class of the threat: the character's class, or the class itself for
+    /// class references that matched no character.
+    pub class: Option<Class>,
+    /// Sum of the matchup weights of all contributors.
+    pub score: u32,
+    /// Names of the selected characters that list this threat.
+    pub contributors: Vec<String>,
+    /// Whether the threat is in the player's roster, once marked by
+    /// [`Roster::mark_threats`](crate::Roster::mark_threats).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owned: Option<bool>,
+}
+
+impl Threat {
+    /// Number of selected characters this threat counters.
+    pub fn count(&self) -> usize {
+        self.contributors.len()
+    }
+}
+
+/// A reverse lookup result: something that counters a given character.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Counter {
+    pub matchup: MatchupRef,
+    /// Sum of the matchup weights from both directions plus any elemental bonus.
+    pub score: u32,
+    /// Whether the counter is in the player's roster, once marked by
+    /// [`Roster::mark_counters`](crate::Roster::mark_counters).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owned: Option<bool>,
+}
+
+/// The result of resolving user-supplied names, see [`CharacterDb::select`].
+#[derive(Debug, Clone)]
+pub struct Selection<'db, 'a> {
+    pub characters: Vec<&'db Character>,
+    pub unknown: Vec<&'a str>,
+}
+
+/// Index of the character named `name_or_alias`. Exact names win over exact
+/// aliases, which win over normalized matches (see [`normalize`]), which win
+/// over kana/romaji readings; among readings the one matching the most kana wins.
+pub(crate) fn position_of(characters: &[Character], name_or_alias: &str) -> Option<usize> {
+    let normalized = normalize(name_or_alias);
+    characters
+        .iter()
+        .position(|c| c.name == name_or_alias)
+        .or_else(|| characters.iter().position(|c| c.matches_exactly(name_or_alias)))
+        .or_else(|| characters.iter().position(|c| c.names().any(|name| normalize(name) == normalized)))
+        .or_else(|| {
+            let readings = characters.iter().enumerate().filter_map(|(i, c)| Some((c.matches_reading(&normalized)?, i)));
+            readings.max_by_key(|(matched, _)| *matched).map(|(_, i)| i)
+        })
+}
+
+/// In-memory character database with name/alias lookup and meta search.
+#[derive(Debug, Clone, Default)]
+pub struct CharacterDb {
+    characters: Vec<Character>,
+    affinity: AffinityTable,
+}
+
+impl CharacterDb {
+    /// Builds the database and resolves every matchup entry against it.
+    pub fn new(characters: Vec<Character>) -> Self {
+        let mut db = CharacterDb { characters, affinity: AffinityTable::default() };
+        let resolve_all = |matchups: &[Matchup]| -> Vec<Matchup> {
+            matchups.iter().map(|m| Matchup::new(db.resolve(m.target.raw()), m.weight)).collect()
+        };
+        let resolved: Vec<(Vec<Matchup>, Vec<Matchup>)> = db
+            .characters
+            .iter()
+            .map(|c| (resolve_all(&c.advantages), resolve_all(&c.disadvantages)))
+            .collect();
+        for (character, (advantages, disadvantages)) in db.characters.iter_mut().zip(resolved) {
+            character.advantages = advantages;
+            character.disadvantages = disadvantages;
+        }
+        db
+    }
+
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        Ok(CharacterDb::new(load_characters(file_path)?))
+    }
+
+    /// Replaces the element affinity table used to rank threats and counters.
+    pub fn with_affinity(mut self, affinity: AffinityTable) -> Self {
+        self.affinity = affinity;
+        self
+    }
+
+    pub fn affinity(&self) -> &AffinityTable {
+        &self.affinity
+    }
+
+    pub fn characters(&self) -> &[Character] {
+        &self.characters
+    }
+
+    /// Looks up a character by name or alias, see [`position_of`].
+    pub fn find(&self, name_or_alias: &str) -> Option<&Character> {
+        position_of(&self.characters, name_or_alias).map(|index| &self.characters[index])
+    }
+
+    /// Like [`CharacterDb::find`], but fails with [`Error::UnknownCharacter`]
+    /// carrying suggestions.
+    pub fn get(&self, name_or_alias: &str) -> Result<&Character> {
+        self.find(name_or_alias).ok_or_else(|| self.not_found(name_or_alias))
+    }
+
+    /// The error for an input that names no character, with up to three suggestions.
+    pub fn not_found(&self, name_or_alias: &str) -> Error {
+        Error::UnknownCharacter {
+            name: name_or_alias.to_string(),
+            suggestions: self.suggest(name_or_alias, 3).into_iter().map(|c| c.name.clone()).collect(),
+        }
+    }
+
+    /// Characters whose name or alias is close to `input`, closest first.
+    pub fn suggest(&self, input: &str, limit: usize) -> Vec<&Character> {
+        let input = normalize(input);
+        let mut candidates: Vec<(usize, &Character)> = self
+            .characters
+            .iter()
+            .filter_map(|c| {
+                let by_name = c.names().filter_map(|name| suggestion_distance(&input, &normalize(name)));
+                let by_reading = reading_key(&input)
+                    .zip(reading_key(&c.name))
+                    .and_then(|(input, name)| suggestion_distance(&input, &name));
+                let distance = by_name.chain(by_reading).min()?;
+                Some((distance, c))
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+        candidates.into_iter().take(limit).map(|(_, c)| c).collect()
+    }
+
+    /// Resolves a matchup entry: a character name or alias wins over a class name.
+    pub fn resolve(&self, raw: &str) -> MatchupRef {
+        if let Some(character) = self.find(raw) {
+            MatchupRef::Character(character.name.clone())
+        } else if let Ok(class) = raw.parse::<Class>() {
+            MatchupRef::Class(class)
+        } else {
+            MatchupRef::Unresolved(raw.to_string())
+        }
+    }
+
+    /// Resolves every name in `names_or_aliases` up front. Inputs that name no
+    /// character end up in `unknown`; repeated characters are kept once.
+    pub fn select<'a>(&self, names_or_aliases: &[&'a str]) -> Selection<'_, 'a> {
+        let mut selection = Selection { characters: Vec::new(), unknown: Vec::new() };
+        for &input in names_or_aliases.iter().filter(|input| !input.trim().is_empty()) {
+            match self.find(input) {
+                Some(character) => {
+                    if !selection.characters.iter().any(|c| c.name == character.name) {
+                        selection.characters.push(character);
+                    }
+                }
+                None => selection.unknown.push(input),
+            }
+        }
+        selection
+    }
+
+    /// Ranks what the selected characters are weak to. Names that do not
+    /// resolve are ignored; use [`CharacterDb::select`] to report them.
+    pub fn find_disadvantages(&self, selected: &[&str]) -> Vec<Threat> {
+        self.find_disadvantages_of(&self.select(selected).characters)
+    }
+
+    /// Characters whose `class` is `class`.
+    pub fn members_of(&self, class: Class) -> impl Iterator<Item = &Character> {
+        self.characters.iter().filter(move |c| c.class == Some(class))
+    }
+
+    /// The class a matchup reference belongs to, if known.
+    pub fn class_of(&self, matchup: &MatchupRef) -> Option<Class> {
+        match matchup {
+            MatchupRef::Character(name) => self.find(name).and_then(|c| c.class),
+            MatchupRef::Class(class) => Some(*class),
+            MatchupRef::Unresolved(_) => None,
+        }
+    }
+
+    /// Expands a class reference into references to its members. Character and
+    /// unresolved references, and classes without members, are returned as is.
+    pub fn expand(&self, matchup: &MatchupRef) -> Vec<MatchupRef> {
+        if let MatchupRef::Class(class) = matchup {
+            let members: Vec<MatchupRef> =
+                self.members_of(*class).map(|c| MatchupRef::Character(c.name.clone())).collect();
+            if !members.is_empty() {
+                return members;
+            }
+        }
+        vec![matchup.clone()]
+    }
+
+    /// Ranks what `selected` is weak to.
+    ///
+    /// Each threat's score is the sum of the matchup weights from every
+    /// selected character that lists it in `disadvantages`. Class entries count
+    /// towards every character of that class, and unselected characters with an
+    /// elemental advantage over a selected character add the affinity bonus.
+    pub fn find_disadvantages_of(&self, selected: &[&Character]) -> Vec<Threat> {
+        let mut threats: HashMap<MatchupRef, Threat> = HashMap::new();
+        let mut add = |target: MatchupRef, weight: u32, contributor: &str| {
+            let threat = threats.entry(target.clone()).or_insert_with(|| Threat {
+                class: self.class_of(&target),
+                matchup: target,
+                score: 0,
+                contributors: Vec::new(),
+                owned: None,
+            });
+            threat.score = threat.score.saturating_add(weight);
+            if !threat.contributors.iter().any(|c| c == contributor) {
+                threat.contributors.push(contributor.to_string());
+            }
+        };
+
+        for character in selected {
+            for disadvantage in character.disadvantages.iter().filter(|d| !d.is_empty()) {
+                for target in self.expand(&disadvantage.target) {
+                    add(target, disadvantage.weight, &character.name);
+                }
+            }
+            // Teammates are not threats to their own team
+            let opponents = self.characters.iter().filter(|other| !selected.iter().any(|s| s.name == other.name));
+            for other in opponents {
+                let bonus = self.affinity.bonus(other.element, character.element);
+                if bonus > 0 {
+                    add(MatchupRef::Character(other.name.clone()), bonus, &character.name);
+                }
+            }
+        }
+
+        let mut threats: Vec<Threat> = threats.into_values().collect();
+
+        // Highest score first; ties broken by contributor count, then name
+        threats.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.count().cmp(&a.count()))
+                .then_with(|| a.matchup.raw().cmp(b.matchup.raw()))
+        });
+        threats
+    }
+
+    /// Finds who counters `name_or_alias`: characters that list it (or its
+    /// class) in their `advantages`, its own `disadvantages` with classes
+    /// expanded to their members, and characters with an elemental advantage
+    /// over it. Duplicates are merged by adding up their scores; the highest
+    /// score comes first.
+    ///
+    /// Returns `None` if the character does not exist.
+    pub fn find_counters(&self, name_or_alias: &str) -> Option<Vec<Counter>> {
+        let target = self.find(name_or_alias)?;
+        let target_ref = MatchupRef::Character(target.name.clone());
+        let class_ref = target.class.map(MatchupRef::Class);
+        let mut counters: Vec<Counter> = Vec::new();
+        let mut add = |matchup: MatchupRef, score: u32| match counters.iter_mut().find(|c| c.matchup == matchup) {
+            Some(counter) => counter.score = counter.score.saturating_add(score),
+            None => counters.push(Counter { matchup, score, owned: None }),
+        };
+
+        for character in &self.characters {
+            let listed = character
+                .advantages
+                .iter()
+                .filter(|m| m.target == target_ref || Some(&m.target) == class_ref.as_ref());
+            for advantage in listed {
+                add(MatchupRef::Character(character.name.clone()), advantage.weight);
+            }
+        }
+        for disadvantage in target.disadvantages.iter().filter(|d| !d.is_empty()) {
+            for counter in self.expand(&disadvantage.target) {
+                add(counter, disadvantage.weight);
+            }
+        }
+        for character in &self.characters {
+            let bonus = self.affinity.bonus(character.element, target.element);
+            if bonus > 0 {
+                add(MatchupRef::Character(character.name.clone()), bonus);
+            }
+        }
+
+        // Stable sort keeps discovery order among equal scores
+        counters.sort_by_key(|counter| Reverse(counter.score));
+        Some(counters)
+    }
+
+    /// Groups threats by class, in [`Class::ALL`] order, with threats of unknown
+    /// class last. The order within each group is preserved.
+    pub fn group_by_class<'t>(&self, threats: &'t [Threat]) -> Vec<(Option<Class>, Vec<&'t Threat>)> {
+        let classes = Class::ALL.iter().map(|&class| Some(class)).chain(std::iter::once(None));
+        classes
+            .map(|class| (class, threats.iter().filter(|t| t.class == class).collect::<Vec<_>>()))
+            .filter(|(_, group)| !group.is_empty())
+            .collect()
+    }
+}
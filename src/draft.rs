@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::character::Character;
+use crate::db::{CharacterDb, Threat};
+use crate::matchup::MatchupRef;
+
+/// One of the two drafting sides; `First` makes the first pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    First,
+    Second,
+}
+
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::First => Side::Second,
+            Side::Second => Side::First,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Side::First => 0,
+            Side::Second => 1,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::First => f.write_str("Side 1"),
+            Side::Second => f.write_str("Side 2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Ban before picking starts; the character is unavailable to both sides.
+    PreBan,
+    Pick,
+    /// Ban one of the opponent's picks after picking ends.
+    FinalBan,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::PreBan => f.write_str("pre-ban"),
+            Action::Pick => f.write_str("pick"),
+            Action::FinalBan => f.write_str("final ban"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub side: Side,
+    pub action: Action,
+}
+
+/// Shape of a draft: bans per side before picking, picks per side in snake
+/// order (1-2-2-...-1), and bans per side on the opponent's picks at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DraftFormat {
+    pub pre_bans: usize,
+    pub picks: usize,
+    pub final_bans: usize,
+}
+
+impl Default for DraftFormat {
+    fn default() -> Self {
+        DraftFormat { pre_bans: 2, picks: 5, final_bans: 1 }
+    }
+}
+
+impl DraftFormat {
+    /// The full sequence of steps, in order.
+    pub fn steps(&self) -> Vec<Step> {
+        let alternate = |count: usize, action: Action| {
+            (0..count * 2).map(move |i| Step {
+                side: if i % 2 == 0 { Side::First } else { Side::Second },
+                action,
+            })
+        };
+        // Snake order: the first pick takes one, then sides alternate taking two
+        // until the second pick takes the last one.
+        let picks = (0..self.picks * 2).map(|i| Step {
+            side: if i.div_ceil(2) % 2 == 0 { Side::First } else { Side::Second },
+            action: Action::Pick,
+        });
+        alternate(self.pre_bans, Action::PreBan)
+            .chain(picks)
+            .chain(alternate(self.final_bans, Action::FinalBan))
+            .collect()
+    }
+
+    /// Checks that a draft among `characters` characters can be played to the
+    /// end: every ban and pick takes a different character, and each final ban
+    /// needs an opponent pick to remove.
+    pub fn check(&self, characters: usize) -> Result<(), DraftError> {
+        if self.final_bans > self.picks {
+            return Err(DraftError::TooManyFinalBans { final_bans: self.final_bans, picks: self.picks });
+        }
+        let needed = (self.pre_bans + self.picks) * 2;
+        if needed > characters {
+            return Err(DraftError::TooFewCharacters { needed, available: characters });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DraftError {
+    Finished,
+    UnknownCharacter(String),
+    /// Already picked or banned.
+    Unavailable(String),
+    /// A final ban must target one of the opponent's remaining picks.
+    NotOpponentPick(String),
+    /// The format takes more characters than there are.
+    TooFewCharacters { needed: usize, available: usize },
+    /// The format has more final bans per side than picks per side.
+    TooManyFinalBans { final_bans: usize, picks: usize },
+}
+
+impl fmt::Display for DraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftError::Finished => write!(f, "the draft is already finished"),
+            DraftError::UnknownCharacter(name) => write!(f, "unknown character: {}", name),
+            DraftError::Unavailable(name) => write!(f, "{} has already been picked or banned", name),
+            DraftError::NotOpponentPick(name) => write!(f, "{} is not one of the opponent's picks", name),
+            DraftError::TooFewCharacters { needed, available } => {
+                write!(f, "the draft needs {} characters, but there are only {}", needed, available)
+            }
+            DraftError::TooManyFinalBans { final_bans, picks } => {
+                write!(f, "final bans per side ({}) exceed picks per side ({})", final_bans, picks)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DraftError {}
+
+/// State of a pick/ban draft between two sides.
+#[derive(Debug, Clone)]
+pub struct Draft<'db> {
+    db: &'db CharacterDb,
+    steps: Vec<Step>,
+    turn: usize,
+    picks: [Vec<&'db Character>; 2],
+    pre_bans: [Vec<&'db Character>; 2],
+    final_bans: [Vec<&'db Character>; 2],
+    /// `counters[defender][attacker]`: counter scores, computed once up front.
+    counters: HashMap<String, HashMap<String, u32>>,
+}
+
+impl<'db> Draft<'db> {
+    /// Starts a draft among the characters of `db`, see [`DraftFormat::check`].
+    pub fn new(db: &'db CharacterDb, format: DraftFormat) -> Result<Self, DraftError> {
+        format.check(db.characters().len())?;
+        Ok(Draft {
+            db,
+            steps: format.steps(),
+            turn: 0,
+            picks: [Vec::new(), Vec::new()],
+            pre_bans: [Vec::new(), Vec::new()],
+            final_bans: [Vec::new(), Vec::new()],
+            counters: db
+                .characters()
+                .iter()
+                .map(|c| {
+                    let counters = db.find_counters(&c.name).unwrap_or_default();
+                    let scores = counters.into_iter().map(|counter| (counter.matchup.raw().to_string(), counter.score));
+                    (c.name.clone(), scores.collect())
+                })
+                .collect(),
+        })
+    }
+
+    /// The step waiting to be played, or `None` once the draft is over.
+    pub fn current(&self) -> Option<Step> {
+        self.steps.get(self.turn).copied()
+    }
+
+    /// Number of steps played so far and in total.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.turn, self.steps.len())
+    }
+
+    pub fn picks(&self, side: Side) -> &[&'db Character] {
+        &self.picks[side.index()]
+    }
+
+    pub fn pre_bans(&self, side: Side) -> &[&'db Character] {
+        &self.pre_bans[side.index()]
+    }
+
+    /// The picks of `side` that the opponent removed with a final ban.
+    pub fn final_bans(&self, side: Side) -> &[&'db Character] {
+        &self.final_bans[side.index()]
+    }
+
+    /// The picks of `side` that survived the final bans.
+    pub fn team(&self, side: Side) -> Vec<&'db Character> {
+        let banned = self.final_bans(side);
+        self.picks(side).iter().copied().filter(|c| !banned.iter().any(|b| b.name == c.name)).collect()
+    }
+
+    /// True if nobody has picked or pre-banned `character` yet.
+    pub fn is_available(&self, character: &Character) -> bool {
+        let taken = self.picks.iter().chain(&self.pre_bans).flatten();
+        !taken.into_iter().any(|c| c.name == character.name)
+    }
+
+    /// Plays the current step with the character named `name_or_alias`.
+    pub fn play(&mut self, name_or_alias: &str) -> Result<&'db Character, DraftError> {
+        let step = self.current().ok_or(DraftError::Finished)?;
+        let character =
+            self.db.find(name_or_alias).ok_or_else(|| DraftError::UnknownCharacter(name_or_alias.to_string()))?;
+        let side = step.side.index();
+        let opponent = step.side.opponent().index();
+        match step.action {
+            Action::PreBan | Action::Pick if !self.is_available(character) => {
+                return Err(DraftError::Unavailable(character.name.clone()));
+            }
+            Action::PreBan => self.pre_bans[side].push(character),
+            Action::Pick => self.picks[side].push(character),
+            Action::FinalBan => {
+                if !self.team(step.side.opponent()).iter().any(|c| c.name == character.name) {
+                    return Err(DraftError::NotOpponentPick(character.name.clone()));
+                }
+                self.final_bans[opponent].push(character);
+            }
+        }
+        self.turn += 1;
+        Ok(character)
+    }
+
+    /// What `side`'s current team is weak to, leaving out characters that can
+    /// no longer be fielded against it.
+    pub fn threats(&self, side: Side) -> Vec<Threat> {
+        let opponent = side.opponent();
+        let opponent_team = self.team(opponent);
+        self.db
+            .find_disadvantages_of(&self.team(side))
+            .into_iter()
+            .filter(|threat| match &threat.matchup {
+                MatchupRef::Character(name) => match self.db.find(name) {
+                    Some(c) => self.is_available(c) || opponent_team.iter().any(|o| o.name == c.name),
+                    None => true,
+                },
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Ranks candidates for the current step, best first.
+    ///
+    /// Picks favour characters that counter the opponent's picks and are not
+    /// countered by them. Pre-bans remove the characters that counter the most
+    /// of the roster overall. Final bans remove the opponent pick that counters
+    /// the acting side the hardest.
+    pub fn suggestions(&self, limit: usize) -> Vec<(&'db Character, i64)> {
+        let Some(step) = self.current() else {
+            return Vec::new();
+        };
+        let own = self.team(step.side);
+        let opponent = self.team(step.side.opponent());
+        let mut ranked: Vec<(&'db Character, i64)> = match step.action {
+            Action::PreBan => self
+                .available()
+                .map(|c| {
+                    let strength: u32 =
+                        self.db.characters().iter().map(|other| self.counter_score(c, other)).fold(0, u32::saturating_add);
+                    (c, i64::from(strength))
+                })
+                .collect(),
+            Action::Pick => self
+                .available()
+                .map(|c| {
+                    let coverage: u32 = opponent.iter().map(|o| self.counter_score(c, o)).fold(0, u32::saturating_add);
+                    let exposure: u32 = opponent.iter().map(|o| self.counter_score(o, c)).fold(0, u32::saturating_add);
+                    (c, i64::from(coverage) - i64::from(exposure))
+                })
+                .collect(),
+            Action::FinalBan => opponent
+                .iter()
+                .map(|&o| {
+                    let pressure: u32 = own.iter().map(|c| self.counter_score(o, c)).fold(0, u32::saturating_add);
+                    (o, i64::from(pressure))
+                })
+                .collect(),
+        };
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        ranked.truncate(limit);
+        ranked
+    }
+
+    fn available(&self) -> impl Iterator<Item = &'db Character> + '_ {
+        let db: &'db CharacterDb = self.db;
+        db.characters().iter().filter(move |c| self.is_available(c))
+    }
+
+    /// How well `attacker` counters `defender` (see [`CharacterDb::find_counters`]).
+    fn counter_score(&self, attacker: &Character, defender: &Character) -> u32 {
+        self.counters.get(&defender.name).and_then(|scores| scores.get(&attacker.name)).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(count: usize) -> CharacterDb {
+        let characters = (1..=count)
+            .map(|i| serde_json::json!({"name": format!("C{}", i), "alias": [], "advantages": [], "disadvantages": []}))
+            .collect();
+        CharacterDb::new(serde_json::from_value(serde_json::Value::Array(characters)).unwrap())
+    }
+
+    #[test]
+    fn orders_steps_in_snake_order() {
+        let format = DraftFormat { pre_bans: 1, picks: 3, final_bans: 1 };
+        let steps: Vec<(Side, Action)> = format.steps().iter().map(|step| (step.side, step.action)).collect();
+        let (first, second) = (Side::First, Side::Second);
+        assert_eq!(
+            steps,
+            [
+                (first, Action::PreBan),
+                (second, Action::PreBan),
+                (first, Action::Pick),
+                (second, Action::Pick),
+                (second, Action::Pick),
+                (first, Action::Pick),
+                (first, Action::Pick),
+                (second, Action::Pick),
+                (first, Action::FinalBan),
+                (second, Action::FinalBan),
+            ]
+        );
+        assert!(DraftFormat { pre_bans: 0, picks: 0, final_bans: 0 }.steps().is_empty());
+    }
+
+    #[test]
+    fn rejects_formats_that_cannot_finish() {
+        let format = DraftFormat::default();
+        assert_eq!(format.check(14), Ok(()));
+        assert_eq!(format.check(13), Err(DraftError::TooFewCharacters { needed: 14, available: 13 }));
+        let format = DraftFormat { pre_bans: 0, picks: 0, final_bans: 1 };
+        assert_eq!(format.check(10), Err(DraftError::TooManyFinalBans { final_bans: 1, picks: 0 }));
+        assert!(Draft::new(&db(3), DraftFormat { pre_bans: 0, picks: 2, final_bans: 0 }).is_err());
+    }
+
+    #[test]
+    fn plays_steps_and_rejects_invalid_choices() {
+        let db = db(4);
+        let mut draft = Draft::new(&db, DraftFormat { pre_bans: 1, picks: 1, final_bans: 1 }).unwrap();
+        draft.play("C1").unwrap();
+        assert_eq!(draft.play("C1"), Err(DraftError::Unavailable("C1".to_string())));
+        assert_eq!(draft.play("C9"), Err(DraftError::UnknownCharacter("C9".to_string())));
+        draft.play("C2").unwrap();
+        draft.play("C3").unwrap();
+        assert_eq!(draft.play("C2"), Err(DraftError::Unavailable("C2".to_string())));
+        draft.play("C4").unwrap();
+
+        // Side 1 may only ban Side 2's pick
+        assert_eq!(draft.play("C3"), Err(DraftError::NotOpponentPick("C3".to_string())));
+        assert_eq!(draft.play("C1"), Err(DraftError::NotOpponentPick("C1".to_string())));
+        draft.play("C4").unwrap();
+        draft.play("C3").unwrap();
+        assert!(draft.team(Side::First).is_empty() && draft.team(Side::Second).is_empty());
+        assert_eq!(draft.progress(), (6, 6));
+        assert_eq!(draft.play("C1"), Err(DraftError::Finished));
+    }
+}
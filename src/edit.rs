@@ -0,0 +1,259 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::character::Character;
+use crate::db::{load_versioned, position_of};
+use crate::error::Result;
+use crate::matchup::{Matchup, MatchupRef};
+use crate::schema::SCHEMA_VERSION;
+use crate::store::save_characters_in;
+
+/// Which matchup list of a character an edit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchupList {
+    Advantages,
+    Disadvantages,
+}
+
+impl FromStr for MatchupList {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "advantage" | "advantages" => Ok(MatchupList::Advantages),
+            "disadvantage" | "disadvantages" => Ok(MatchupList::Disadvantages),
+            other => Err(format!("expected \"advantage\" or \"disadvantage\", got \"{}\"", other)),
+        }
+    }
+}
+
+impl fmt::Display for MatchupList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchupList::Advantages => f.write_str("advantages"),
+            MatchupList::Disadvantages => f.write_str("disadvantages"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    UnknownCharacter(String),
+    DuplicateName(String),
+    DuplicateAlias { character: String, alias: String },
+    MissingAlias { character: String, alias: String },
+    DuplicateMatchup { character: String, list: MatchupList, target: String },
+    MissingMatchup { character: String, list: MatchupList, target: String },
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::UnknownCharacter(name) => write!(f, "unknown character: {}", name),
+            EditError::DuplicateName(name) => write!(f, "a character named {} already exists", name),
+            EditError::DuplicateAlias { character, alias } => {
+                write!(f, "{} already has the alias \"{}\"", character, alias)
+            }
+            EditError::MissingAlias { character, alias } => write!(f, "{} has no alias \"{}\"", character, alias),
+            EditError::DuplicateMatchup { character, list, target } => {
+                write!(f, "{} already lists {} in its {}", character, target, list)
+            }
+            EditError::MissingMatchup { character, list, target } => {
+                write!(f, "{} does not list {} in its {}", character, target, list)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// Edits the raw character list of a data file.
+///
+/// Matchup entries are kept exactly as written (not resolved), and the file
+/// is saved in the schema version it was loaded in, so saving only changes
+/// what was edited. Use [`migrate_file`](crate::migrate_file) to upgrade.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    characters: Vec<Character>,
+    version: u32,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor::new(Vec::new())
+    }
+}
+
+impl Editor {
+    /// An editor that saves in the latest schema version.
+    pub fn new(characters: Vec<Character>) -> Self {
+        Editor { characters, version: SCHEMA_VERSION }
+    }
+
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        let (characters, version) = load_versioned(file_path.as_ref())?;
+        Ok(Editor { characters, version })
+    }
+
+    /// Saves in schema `version` instead, e.g. that of the file the characters came from.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// The schema version the editor saves in.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
+        save_characters_in(file_path.as_ref(), &self.characters, self.version)
+    }
+
+    pub fn characters(&self) -> &[Character] {
+        &self.characters
+    }
+
+    pub fn into_characters(self) -> Vec<Character> {
+        self.characters
+    }
+
+    /// Index of the character named `name_or_alias`, resolved the same way as
+    /// [`CharacterDb::find`](crate::CharacterDb::find).
+    fn index_of(&self, name_or_alias: &str) -> std::result::Result<usize, EditError> {
+        position_of(&self.characters, name_or_alias).ok_or_else(|| EditError::UnknownCharacter(name_or_alias.to_string()))
+    }
+
+    pub fn get(&self, name_or_alias: &str) -> Option<&Character> {
+        self.index_of(name_or_alias).ok().map(|index| &self.characters[index])
+    }
+
+    /// Gives mutable access to a character. Renaming through this does not check
+    /// for duplicate names; use [`Editor::rename`] for that.
+    pub fn get_mut(&mut self, name_or_alias: &str) -> std::result::Result<&mut Character, EditError> {
+        let index = self.index_of(name_or_alias)?;
+        Ok(&mut self.characters[index])
+    }
+
+    pub fn add(&mut self, character: Character) -> std::result::Result<(), EditError> {
+        if self.characters.iter().any(|c| c.name == character.name) {
+            return Err(EditError::DuplicateName(character.name));
+        }
+        self.characters.push(character);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name_or_alias: &str) -> std::result::Result<Character, EditError> {
+        let index = self.index_of(name_or_alias)?;
+        Ok(self.characters.remove(index))
+    }
+
+    /// Renames a character and every matchup entry that refers to it by its old name.
+    pub fn rename(&mut self, name_or_alias: &str, new_name: &str) -> std::result::Result<(), EditError> {
+        let index = self.index_of(name_or_alias)?;
+        if self.characters.iter().enumerate().any(|(i, c)| i != index && c.name == new_name) {
+            return Err(EditError::DuplicateName(new_name.to_string()));
+        }
+        let old_name = std::mem::replace(&mut self.characters[index].name, new_name.to_string());
+        for character in &mut self.characters {
+            let matchups = character.advantages.iter_mut().chain(character.disadvantages.iter_mut());
+            for matchup in matchups.filter(|m| m.target.raw() == old_name) {
+                matchup.target = MatchupRef::Unresolved(new_name.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_alias(&mut self, name_or_alias: &str, alias: &str) -> std::result::Result<(), EditError> {
+        let character = self.get_mut(name_or_alias)?;
+        if character.alias.iter().any(|a| a == alias) {
+            return Err(EditError::DuplicateAlias { character: character.name.clone(), alias: alias.to_string() });
+        }
+        character.alias.push(alias.to_string());
+        Ok(())
+    }
+
+    pub fn remove_alias(&mut self, name_or_alias: &str, alias: &str) -> std::result::Result<(), EditError> {
+        let character = self.get_mut(name_or_alias)?;
+        let before = character.alias.len();
+        character.alias.retain(|a| a != alias);
+        if character.alias.len() == before {
+            return Err(EditError::MissingAlias { character: character.name.clone(), alias: alias.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Adds a matchup entry, or updates its weight if the target is already listed
+    /// with a different one.
+    pub fn add_matchup(
+        &mut self,
+        name_or_alias: &str,
+        list: MatchupList,
+        matchup: Matchup,
+    ) -> std::result::Result<(), EditError> {
+        let character = self.get_mut(name_or_alias)?;
+        let name = character.name.clone();
+        let matchups = matchup_list(character, list);
+        match matchups.iter_mut().find(|m| m.target.raw() == matchup.target.raw()) {
+            Some(existing) if existing.weight == matchup.weight => Err(EditError::DuplicateMatchup {
+                character: name,
+                list,
+                target: matchup.target.raw().to_string(),
+            }),
+            Some(existing) => {
+                existing.weight = matchup.weight;
+                Ok(())
+            }
+            None => {
+                matchups.push(matchup);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove_matchup(
+        &mut self,
+        name_or_alias: &str,
+        list: MatchupList,
+        target: &str,
+    ) -> std::result::Result<(), EditError> {
+        let character = self.get_mut(name_or_alias)?;
+        let name = character.name.clone();
+        let matchups = matchup_list(character, list);
+        let before = matchups.len();
+        matchups.retain(|m| m.target.raw() != target);
+        if matchups.len() == before {
+            return Err(EditError::MissingMatchup { character: name, list, target: target.to_string() });
+        }
+        Ok(())
+    }
+}
+
+fn matchup_list(character: &mut Character, list: MatchupList) -> &mut Vec<Matchup> {
+    match list {
+        MatchupList::Advantages => &mut character.advantages,
+        MatchupList::Disadvantages => &mut character.disadvantages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CharacterDb;
+
+    fn character(name: &str) -> Character {
+        serde_json::from_value(serde_json::json!({"name": name, "alias": [], "advantages": [], "disadvantages": []})).unwrap()
+    }
+
+    #[test]
+    fn resolves_names_like_the_database() {
+        // The fuller reading wins even though the kanji wildcard matches first
+        let characters = vec![character("雪国のソリタリア"), character("ゆきぐにのソリタリア")];
+        let db = CharacterDb::new(characters.clone());
+        let mut editor = Editor::new(characters);
+        let input = "yukiguni no soritaria";
+        assert_eq!(db.find(input).map(|c| c.name.as_str()), Some("ゆきぐにのソリタリア"));
+        assert_eq!(editor.remove(input).unwrap().name, "ゆきぐにのソリタリア");
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::store::read_source;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Element {
+    Fire,
+    Ice,
+    Earth,
+    Light,
+    Dark,
+}
+
+impl Element {
+    pub const ALL: [Element; 5] = [Element::Fire, Element::Ice, Element::Earth, Element::Light, Element::Dark];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Element::Fire => "Fire",
+            Element::Ice => "Ice",
+            Element::Earth => "Earth",
+            Element::Light => "Light",
+            Element::Dark => "Dark",
+        }
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Element {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Element::ALL
+            .iter()
+            .copied()
+            .find(|element| element.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown element: {}", s))
+    }
+}
+
+/// Which elements have the upper hand over which, and how much that counts
+/// compared to an explicit matchup entry.
+///
+/// Stored as JSON, e.g. `{"bonus": 1, "advantages": {"Fire": ["Earth"]}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffinityTable {
+    /// Score added for each elemental advantage.
+    pub bonus: u32,
+    /// Maps an attacking element to the elements it is strong against.
+    pub advantages: BTreeMap<Element, Vec<Element>>,
+}
+
+impl AffinityTable {
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        let source = read_source(file_path.as_ref())?;
+        serde_json::from_str(&source).map_err(|err| Error::from(err).in_file(file_path))
+    }
+
+    /// A table without any affinities, which leaves rankings unchanged.
+    pub fn none() -> Self {
+        AffinityTable { bonus: 0, advantages: BTreeMap::new() }
+    }
+
+    pub fn has_advantage(&self, attacker: Element, defender: Element) -> bool {
+        self.advantages.get(&attacker).is_some_and(|strong| strong.contains(&defender))
+    }
+
+    /// The bonus `attacker` gets against `defender`, if both elements are known.
+    pub fn bonus(&self, attacker: Option<Element>, defender: Option<Element>) -> u32 {
+        match (attacker, defender) {
+            (Some(attacker), Some(defender)) if self.has_advantage(attacker, defender) => self.bonus,
+            _ => 0,
+        }
+    }
+}
+
+impl Default for AffinityTable {
+    /// The elemental triangle (fire > earth > ice > fire) plus light and dark
+    /// being strong against each other.
+    fn default() -> Self {
+        let advantages = BTreeMap::from([
+            (Element::Fire, vec![Element::Earth]),
+            (Element::Earth, vec![Element::Ice]),
+            (Element::Ice, vec![Element::Fire]),
+            (Element::Light, vec![Element::Dark]),
+            (Element::Dark, vec![Element::Light]),
+        ]);
+        AffinityTable { bonus: 1, advantages }
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::draft::DraftError;
+use crate::edit::EditError;
+use crate::merge::Conflict;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A data, roster or affinity file does not exist.
+    FileNotFound(PathBuf),
+    Io(io::Error),
+    /// Malformed or mistyped data. `file` is set once the error is tied to a
+    /// file, see [`Error::in_file`]; `line` is 0 when the position is unknown.
+    Parse { file: Option<PathBuf>, line: usize, column: usize, message: String },
+    /// A name or alias that matches no character, with close matches.
+    UnknownCharacter { name: String, suggestions: Vec<String> },
+    /// The data has this many validation issues.
+    Validation(usize),
+    Edit(EditError),
+    /// Two data files define the same character differently.
+    Conflict(Conflict),
+    /// An edit was attempted on merged data, which has no single file to write to.
+    NotEditable,
+    /// The named command reads its own input from standard input, so the data
+    /// cannot come from there too.
+    StdinInUse(&'static str),
+    /// A draft format that cannot be played with the characters at hand.
+    Draft(DraftError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Attaches the file a parse error came from.
+    pub fn in_file<P: AsRef<Path>>(self, path: P) -> Self {
+        match self {
+            Error::Parse { file: None, line, column, message } => {
+                Error::Parse { file: Some(path.as_ref().to_path_buf()), line, column, message }
+            }
+            err => err,
+        }
+    }
+
+    /// Process exit code for this error, distinct per variant so scripts can
+    /// tell failures apart: 2 file not found, 3 I/O error, 4 parse error,
+    /// 5 unknown character, 6 validation failure, 7 rejected edit, 8 conflicting
+    /// definitions, 9 standard input needed by the command, 10 unplayable draft.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::FileNotFound(_) => 2,
+            Error::Io(_) => 3,
+            Error::Parse { .. } => 4,
+            Error::UnknownCharacter { .. } | Error::Edit(EditError::UnknownCharacter(_)) => 5,
+            Error::Validation(_) => 6,
+            Error::Edit(_) | Error::NotEditable => 7,
+            Error::Conflict(_) => 8,
+            Error::StdinInUse(_) => 9,
+            Error::Draft(_) => 10,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::FileNotFound(path) => write!(f, "file not found: {}", path.display()),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Parse { file: Some(file), line: 0, message, .. } => {
+                write!(f, "invalid character data in {}: {}", file.display(), message)
+            }
+            Error::Parse { file: None, line: 0, message, .. } => write!(f, "invalid character data: {}", message),
+            Error::Parse { file: Some(file), line, column, message } => {
+                write!(f, "invalid character data at {}:{}:{}: {}", file.display(), line, column, message)
+            }
+            Error::Parse { file: None, line, column, message } => {
+                write!(f, "invalid character data at line {} column {}: {}", line, column, message)
+            }
+            Error::UnknownCharacter { name, suggestions } if suggestions.is_empty() => {
+                write!(f, "unknown character: {}", name)
+            }
+            Error::UnknownCharacter { name, suggestions } => {
+                write!(f, "unknown character: {} (did you mean {}?)", name, suggestions.join(", "))
+            }
+            Error::Validation(count) => write!(f, "{} validation issue(s) found", count),
+            Error::Edit(err) => write!(f, "{}", err),
+            Error::Conflict(conflict) => write!(f, "conflicting definitions: {}", conflict),
+            Error::NotEditable => write!(f, "merged or piped data cannot be edited; pass a single data file with --file"),
+            Error::StdinInUse(command) => {
+                write!(f, "{} reads from standard input; pass a data file with --file instead of -", command)
+            }
+            Error::Draft(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Edit(err) => Some(err),
+            Error::Draft(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        let (line, column) = (err.line(), err.column());
+        let message = err.to_string();
+        // serde_json appends the position to its message; it is shown separately
+        let suffix = format!(" at line {} column {}", line, column);
+        let message = message.strip_suffix(&suffix).unwrap_or(&message).to_string();
+        Error::Parse { file: None, line, column, message }
+    }
+}
+
+impl From<EditError> for Error {
+    fn from(err: EditError) -> Self {
+        Error::Edit(err)
+    }
+}
+
+impl From<DraftError> for Error {
+    fn from(err: DraftError) -> Self {
+        Error::Draft(err)
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde_json::{Map, Value};
+
+use crate::{error, toml, yaml};
+
+/// File format of character data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataFormat {
+    #[default]
+    Json,
+    Yaml,
+    /// A `[[characters]]` array of tables.
+    Toml,
+}
+
+impl DataFormat {
+    pub const VARIANTS: [&'static str; 3] = ["json", "yaml", "toml"];
+
+    /// Detects the format from the file extension. Anything that is not
+    /// `.yaml`/`.yml` or `.toml` is read as JSON.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Parses `source` without interpreting it as characters.
+    pub(crate) fn parse(self, source: &str) -> error::Result<Value> {
+        match self {
+            DataFormat::Json => Ok(serde_json::from_str(source)?),
+            DataFormat::Yaml => yaml::parse(source),
+            DataFormat::Toml => toml::parse(source),
+        }
+    }
+
+    /// Guesses the format of data that has no file name, such as standard
+    /// input, from its first line: `[[table]]`, `[table]` or `key = value` is
+    /// TOML, other `[` or `{` is JSON, and anything else YAML.
+    pub fn detect(source: &str) -> Self {
+        let first = source
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .unwrap_or_default();
+        let is_key = |key: &str| !key.is_empty() && key.chars().all(is_bare_key);
+        let table_header = first.starts_with("[[")
+            || first.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).is_some_and(is_key);
+        if table_header || first.split_once('=').is_some_and(|(key, _)| is_key(key.trim())) {
+            DataFormat::Toml
+        } else if first.starts_with('[') || first.starts_with('{') {
+            DataFormat::Json
+        } else {
+            DataFormat::Yaml
+        }
+    }
+}
+
+fn is_bare_key(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+impl FromStr for DataFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(DataFormat::Json),
+            "yaml" | "yml" => Ok(DataFormat::Yaml),
+            "toml" => Ok(DataFormat::Toml),
+            other => Err(format!("unknown data format: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataFormat::Json => "json",
+            DataFormat::Yaml => "yaml",
+            DataFormat::Toml => "toml",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Field order for YAML and TOML output: `serde_json::Value` maps are sorted by
+/// key, so writers put the document and `Character` fields back in declaration
+/// order.
+const FIELD_ORDER: [&str; 9] = ["version", "characters", "name", "alias", "advantages", "disadvantages", "class", "element", "weight"];
+
+/// Entries of `map` with known fields first, in [`FIELD_ORDER`].
+pub(crate) fn ordered(map: &Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| FIELD_ORDER.iter().position(|field| field == key).unwrap_or(FIELD_ORDER.len()));
+    entries
+}
@@ -0,0 +1,334 @@
+//! Kana/romaji folding for name lookups.
+//!
+//! Names are compared through a loose hiragana "reading key": katakana is
+//! folded to hiragana, romaji input is converted to hiragana, and differences
+//! that romanizations usually blur (long vowels, doubled consonants, ヴ vs
+//! バ-row, ぢ/じ, ...) are folded away. Kanji have no reading we could
+//! derive, so in a name they act as wildcards for a few kana each:
+//! "yukiguni no soritaria" matches 雪国のソリタリア.
+
+/// Minimum number of kana a name must contain before it can be matched by reading,
+/// so names that are mostly kanji do not match almost anything.
+const MIN_LITERAL_KANA: usize = 3;
+
+/// Most kana a single kanji can stand for when matching by reading.
+const MAX_KANA_PER_KANJI: usize = 4;
+
+/// Part of a name's reading pattern.
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    /// Folded kana that must match literally.
+    Kana(Vec<char>),
+    /// A run of this many kanji, matching 1 to [`MAX_KANA_PER_KANJI`] kana each.
+    Kanji(usize),
+}
+
+/// Folds katakana to hiragana, leaving everything else untouched.
+pub fn to_hiragana(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Converts romaji to hiragana. Returns `None` if `s` contains Latin letters
+/// that do not form valid romaji (e.g. "ml.eda").
+pub fn romaji_to_hiragana(s: &str) -> Option<String> {
+    let chars: Vec<char> = s.to_lowercase().chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if !c.is_ascii_alphabetic() {
+            out.push(if c == '-' { 'ー' } else { c });
+            i += 1;
+            continue;
+        }
+        let next = chars.get(i + 1).copied();
+        if c == 'n' {
+            match next {
+                Some('\'') => {
+                    out.push('ん');
+                    i += 2;
+                    continue;
+                }
+                // "nna" reads as んな, a trailing or pre-consonant "nn" as a single ん
+                Some('n') => {
+                    out.push('ん');
+                    let after = chars.get(i + 2).copied();
+                    i += if after.is_some_and(|a| is_vowel(a) || a == 'y') { 1 } else { 2 };
+                    continue;
+                }
+                Some(n) if is_vowel(n) || n == 'y' => {}
+                _ => {
+                    out.push('ん');
+                    i += 1;
+                    continue;
+                }
+            }
+        }
+        if (Some(c) == next && !is_vowel(c)) || (c == 't' && next == Some('c')) {
+            out.push('っ');
+            i += 1;
+            continue;
+        }
+        let (kana, len) = (1..=3)
+            .rev()
+            .filter(|len| i + len <= chars.len())
+            .find_map(|len| {
+                let syllable: String = chars[i..i + len].iter().collect();
+                ROMAJI.iter().find(|(romaji, _)| *romaji == syllable).map(|(_, kana)| (*kana, len))
+            })?;
+        out.push_str(kana);
+        i += len;
+    }
+    Some(out)
+}
+
+/// Loose hiragana key of a name or user input. Latin input is read as romaji;
+/// `None` means it could not be.
+pub fn reading_key(s: &str) -> Option<String> {
+    let hiragana = if s.chars().any(|c| c.is_ascii_alphabetic()) {
+        romaji_to_hiragana(s)?
+    } else {
+        to_hiragana(s)
+    };
+    Some(fold(&hiragana))
+}
+
+/// Returns the number of kana `input` matched against `name`'s reading, or
+/// `None` if it does not match. Each kanji in `name` matches one to
+/// [`MAX_KANA_PER_KANJI`] kana.
+pub fn reading_match(input: &str, name: &str) -> Option<usize> {
+    let input: Vec<char> = reading_key(input)?.chars().collect();
+    let pattern = name_pattern(name);
+    let literal: usize = pattern
+        .iter()
+        .map(|segment| match segment {
+            Segment::Kana(kana) => kana.len(),
+            Segment::Kanji(_) => 0,
+        })
+        .sum();
+    if literal < MIN_LITERAL_KANA || input.is_empty() {
+        return None;
+    }
+    matches_pattern(&input, &pattern).then_some(literal)
+}
+
+/// Splits a name into literal kana runs and kanji wildcards.
+fn name_pattern(name: &str) -> Vec<Segment> {
+    let mut pattern: Vec<Segment> = Vec::new();
+    let mut literal = String::new();
+    for c in name.chars() {
+        if is_kanji(c) {
+            if !literal.is_empty() {
+                pattern.push(Segment::Kana(fold(&to_hiragana(&literal)).chars().collect()));
+                literal.clear();
+            }
+            match pattern.last_mut() {
+                Some(Segment::Kanji(count)) => *count += 1,
+                _ => pattern.push(Segment::Kanji(1)),
+            }
+        } else {
+            literal.push(c);
+        }
+    }
+    if !literal.is_empty() {
+        pattern.push(Segment::Kana(fold(&to_hiragana(&literal)).chars().collect()));
+    }
+    pattern
+}
+
+fn matches_pattern(input: &[char], pattern: &[Segment]) -> bool {
+    match pattern.split_first() {
+        None => input.is_empty(),
+        Some((Segment::Kana(literal), rest)) => {
+            input.starts_with(literal) && matches_pattern(&input[literal.len()..], rest)
+        }
+        Some((Segment::Kanji(count), rest)) => {
+            let longest = input.len().min(count * MAX_KANA_PER_KANJI);
+            (*count..=longest).any(|skip| matches_pattern(&input[skip..], rest))
+        }
+    }
+}
+
+fn fold(hiragana: &str) -> String {
+    let mut out: Vec<char> = Vec::new();
+    let mut chars = hiragana.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            'ゔ' => match chars.peek() {
+                Some(&small @ ('ぁ' | 'ぃ' | 'ぇ' | 'ぉ')) => {
+                    chars.next();
+                    match small {
+                        'ぁ' => 'ば',
+                        'ぃ' => 'び',
+                        'ぇ' => 'べ',
+                        _ => 'ぼ',
+                    }
+                }
+                _ => 'ぶ',
+            },
+            'ぢ' => 'じ',
+            // Doubled consonants are romanized inconsistently, so ignore the geminate mark.
+            'っ' => continue,
+            'づ' => 'ず',
+            'を' => 'お',
+            c => c,
+        };
+        if !is_kana(c) {
+            continue;
+        }
+        // Long vowels: drop a vowel that just repeats the previous one (and おう).
+        let previous_vowel = out.last().and_then(|&p| vowel_of(p));
+        let repeats = match (previous_vowel, c) {
+            (Some(v), 'あ' | 'い' | 'う' | 'え' | 'お') => vowel_of(c) == Some(v) || (v == 'o' && c == 'う'),
+            _ => false,
+        };
+        if !repeats {
+            out.push(c);
+        }
+    }
+    out.into_iter().collect()
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}')
+}
+
+fn is_kanji(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '々')
+}
+
+const KANA_ROWS: [&str; 17] = [
+    "あいうえお",
+    "かきくけこ",
+    "がぎぐげご",
+    "さしすせそ",
+    "ざじずぜぞ",
+    "たちつてと",
+    "だぢづでど",
+    "なにぬねの",
+    "はひふへほ",
+    "ばびぶべぼ",
+    "ぱぴぷぺぽ",
+    "まみむめも",
+    "や\0ゆ\0よ",
+    "らりるれろ",
+    "わ\0\0\0を",
+    "ぁぃぅぇぉ",
+    "ゃ\0ゅ\0ょ",
+];
+
+fn vowel_of(kana: char) -> Option<char> {
+    KANA_ROWS
+        .iter()
+        .find_map(|row| row.chars().position(|k| k == kana))
+        .map(|index| ['a', 'i', 'u', 'e', 'o'][index])
+}
+
+const ROMAJI: &[(&str, &str)] = &[
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("ca", "か"), ("cu", "く"), ("co", "こ"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("sa", "さ"), ("si", "し"), ("shi", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("za", "ざ"), ("zi", "じ"), ("ji", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("ta", "た"), ("ti", "ち"), ("chi", "ち"), ("tu", "つ"), ("tsu", "つ"), ("te", "て"), ("to", "と"),
+    ("da", "だ"), ("di", "でぃ"), ("du", "づ"), ("de", "で"), ("do", "ど"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("ha", "は"), ("hi", "ひ"), ("hu", "ふ"), ("fu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("ya", "や"), ("yu", "ゆ"), ("ye", "いぇ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("la", "ら"), ("li", "り"), ("lu", "る"), ("le", "れ"), ("lo", "ろ"),
+    ("wa", "わ"), ("wi", "うぃ"), ("we", "うぇ"), ("wo", "を"),
+    ("va", "ゔぁ"), ("vi", "ゔぃ"), ("vu", "ゔ"), ("ve", "ゔぇ"), ("vo", "ゔぉ"),
+    ("fa", "ふぁ"), ("fi", "ふぃ"), ("fe", "ふぇ"), ("fo", "ふぉ"),
+    ("thi", "てぃ"), ("dhi", "でぃ"),
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"),
+    ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("sha", "しゃ"), ("shu", "しゅ"), ("she", "しぇ"), ("sho", "しょ"),
+    ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"),
+    ("ja", "じゃ"), ("ju", "じゅ"), ("je", "じぇ"), ("jo", "じょ"),
+    ("jya", "じゃ"), ("jyu", "じゅ"), ("jyo", "じょ"),
+    ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"),
+    ("cha", "ちゃ"), ("chu", "ちゅ"), ("che", "ちぇ"), ("cho", "ちょ"),
+    ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("cya", "ちゃ"), ("cyu", "ちゅ"), ("cyo", "ちょ"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"),
+    ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"),
+    ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"),
+    ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("lya", "りゃ"), ("lyu", "りゅ"), ("lyo", "りょ"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_romaji() {
+        let cases = [
+            ("violeta", "ゔぃおれた"),
+            ("soritaria", "そりたりあ"),
+            ("konnichiwa", "こんにちわ"),
+            ("shinnen", "しんねん"),
+            ("kan'i", "かんい"),
+            ("hon", "ほん"),
+            ("kippu", "きっぷ"),
+            ("matcha", "まっちゃ"),
+            ("Ryuu-", "りゅうー"),
+        ];
+        for (romaji, kana) in cases {
+            assert_eq!(romaji_to_hiragana(romaji).as_deref(), Some(kana), "{}", romaji);
+        }
+        assert_eq!(romaji_to_hiragana("ml.eda"), None);
+        assert_eq!(romaji_to_hiragana("xyz"), None);
+    }
+
+    #[test]
+    fn folds_romanization_differences() {
+        assert_eq!(to_hiragana("ヴィオレタ"), "ゔぃおれた");
+        assert_eq!(reading_key("ヴィオレタ"), reading_key("violeta"));
+        assert_eq!(reading_key("ヴィオレタ"), reading_key("bioreta"));
+        assert_eq!(reading_key("koori"), reading_key("kouri"));
+        assert_eq!(reading_key("kippu"), reading_key("kipu"));
+        assert_eq!(reading_key("ぢ"), reading_key("じ"));
+        assert_ne!(reading_key("soritaria"), reading_key("soritario"));
+    }
+
+    #[test]
+    fn matches_names_by_reading() {
+        assert_eq!(reading_match("violeta", "ヴィオレタ"), Some(4));
+        assert!(reading_match("ヴぃおれた", "ヴィオレタ").is_some());
+        assert!(reading_match("yukiguni no soritaria", "雪国のソリタリア").is_some());
+        assert!(reading_match("ゆきぐにのそりたりあ", "雪国のソリタリア").is_some());
+        assert!(reading_match("ユキグニノソリタリア", "雪国のソリタリア").is_some());
+        assert!(reading_match("zanei no violeta", "残影のヴィオレタ").is_some());
+    }
+
+    #[test]
+    fn does_not_match_other_readings() {
+        assert_eq!(reading_match("violet", "ヴィオレタ"), None);
+        assert_eq!(reading_match("soritaria", "雪国のソリタリア"), None);
+        assert_eq!(reading_match("no violeta", "残影のヴィオレタ"), None);
+        assert_eq!(reading_match("kakikukekokakikukeko no violeta", "残影のヴィオレタ"), None);
+        assert_eq!(reading_match("yukiguni no soritaria", "雪国のヴィオレタ"), None);
+        assert_eq!(reading_match("ml.eda", "雪国のソリタリア"), None);
+        assert_eq!(reading_match("", "ヴィオレタ"), None);
+        // Too few kana to match by reading alone
+        assert_eq!(reading_match("kan", "深淵"), None);
+        assert_eq!(reading_match("shinen no yu", "深淵のユ"), None);
+    }
+}
@@ -0,0 +1,47 @@
+mod character;
+mod class;
+mod csv;
+mod db;
+mod draft;
+mod edit;
+mod element;
+mod error;
+mod format;
+mod kana;
+mod locate;
+mod matching;
+mod matchup;
+mod merge;
+mod output;
+mod overlay;
+mod recommend;
+mod roster;
+mod schema;
+mod store;
+mod toml;
+mod validate;
+mod yaml;
+
+pub use character::Character;
+pub use class::Class;
+pub use csv::{load_csv, parse_csv, CsvImport, RowError};
+pub use db::{
+    load_characters, load_characters_as, parse_characters, parse_characters_as, CharacterDb, Counter, Selection, Threat,
+};
+pub use draft::{Action, Draft, DraftError, DraftFormat, Side, Step};
+pub use edit::{EditError, Editor, MatchupList};
+pub use element::{AffinityTable, Element};
+pub use error::{Error, Result};
+pub use format::DataFormat;
+pub use kana::{reading_key, reading_match, romaji_to_hiragana, to_hiragana};
+pub use locate::Position;
+pub use matching::{edit_distance, normalize};
+pub use matchup::{Matchup, MatchupRef, DEFAULT_WEIGHT};
+pub use merge::{data_files, load_merged, Conflict, Merged, Precedence};
+pub use output::{render_character, render_characters, render_threats, OutputFormat};
+pub use overlay::{Changes, Overlay, OverlayEntry, OverlayReport};
+pub use recommend::{Recommendation, TeamMember};
+pub use roster::{Roster, RosterEntry};
+pub use schema::{migrate_file, Migration, SCHEMA_VERSION};
+pub use store::{save_characters, save_characters_as, to_data_string, to_json_string, STDIN};
+pub use validate::{new_issues, validate, validate_file, Issue, IssueKind};
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// 1-based line/column of a value in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Maps JSON pointers (e.g. `/3/advantages/1`) to the position where each value starts.
+///
+/// This is only used to annotate diagnostics, so it is lenient: scanning stops
+/// at the first syntax error and returns whatever was found up to that point.
+pub(crate) fn json_positions(source: &str) -> HashMap<String, Position> {
+    let mut scanner = Scanner {
+        chars: source.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        positions: HashMap::new(),
+    };
+    scanner.skip_whitespace();
+    let _ = scanner.value(String::new());
+    scanner.positions
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, Position>,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.skip_whitespace();
+        (self.bump()? == expected).then_some(())
+    }
+
+    fn value(&mut self, pointer: String) -> Option<()> {
+        self.skip_whitespace();
+        let position = Position { line: self.line, column: self.column };
+        self.positions.insert(pointer.clone(), position);
+        match self.peek()? {
+            '{' => self.object(pointer),
+            '[' => self.array(pointer),
+            '"' => self.string().map(|_| ()),
+            _ => {
+                while matches!(self.peek(), Some(c) if !c.is_whitespace() && !matches!(c, ',' | ']' | '}')) {
+                    self.bump();
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn object(&mut self, pointer: String) -> Option<()> {
+        self.bump();
+        self.skip_whitespace();
+        if self.peek()? == '}' {
+            self.bump();
+            return Some(());
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            self.value(format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1")))?;
+            self.skip_whitespace();
+            match self.bump()? {
+                ',' => continue,
+                '}' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: String) -> Option<()> {
+        self.bump();
+        self.skip_whitespace();
+        if self.peek()? == ']' {
+            self.bump();
+            return Some(());
+        }
+        let mut index = 0;
+        loop {
+            self.value(format!("{}/{}", pointer, index))?;
+            index += 1;
+            self.skip_whitespace();
+            match self.bump()? {
+                ',' => continue,
+                ']' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.bump()? != '"' {
+            return None;
+        }
+        let mut out = String::new();
+        loop {
+            match self.bump()? {
+                '"' => return Some(out),
+                '\\' => {
+                    let escaped = self.bump()?;
+                    out.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    });
+                }
+                c => out.push(c),
+            }
+        }
+    }
+}
@@ -1,101 +1,801 @@
-use std::fs::File;
-use std::io::{self, BufReader};
-use colored::Colorize;
-use serde::{Deserialize, Serialize};
-use serde_json::from_reader;
+use std::io;
+use std::path::Path;
+use std::process;
+use character_picker::{
+    load_characters, load_characters_as, load_csv, load_merged, migrate_file, new_issues, render_character, render_characters, render_threats,
+    save_characters_as, validate, validate_file, Action, AffinityTable, Character, CharacterDb, Class, Counter,
+    DataFormat, Draft, DraftError, DraftFormat, EditError, Editor, Element, Error, Matchup, MatchupList, MatchupRef,
+    OutputFormat, Overlay, OverlayEntry, OverlayReport, Precedence, Recommendation, Roster, RosterEntry, Side, Threat, SCHEMA_VERSION, STDIN,
+};
+use colored::{ColoredString, Colorize};
 use structopt::StructOpt;
-use std::collections::HashMap;
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Character {
-    name: String,
-    alias: Vec<String>,
-    advantages: Vec<String>,
-    disadvantages: Vec<String>,
-}
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "character_info_system")]
 struct Opt {
-    /// JSON file containing character data
-    #[structopt(short, long, default_value = "characters.json")]
-    file: String,
+    /// Character data file: JSON, YAML (.yaml/.yml) or TOML (.toml). Repeat to merge several
+    /// files; a directory stands for the data files in it, and `-` for standard input
+    #[structopt(short, long, default_value = "characters.json", global = true, number_of_values = 1)]
+    file: Vec<String>,
+
+    /// When merged files define the same character differently: "override" keeps the later
+    /// definition, "error" fails
+    #[structopt(long, default_value = "override", possible_values = &Precedence::VARIANTS, global = true)]
+    on_conflict: Precedence,
+
+    /// Output format for show, list and meta
+    #[structopt(short, long, default_value = "text", possible_values = &OutputFormat::VARIANTS, global = true)]
+    output: OutputFormat,
+
+    /// JSON file with the element affinity table (defaults to the built-in triangle)
+    #[structopt(long, global = true)]
+    affinity: Option<String>,
 
-    /// Display character details by name or alias
-    #[structopt(short, long)]
+    /// JSON file of local changes to aliases and matchups, applied on top of the data
+    #[structopt(long, global = true)]
+    overlay: Option<String>,
+
+    /// JSON file listing the characters you own, to mark or filter results
+    #[structopt(long, global = true)]
+    roster: Option<String>,
+
+    /// With --roster, only show owned characters in threats, counters and suggestions
+    #[structopt(long, global = true)]
+    owned_only: bool,
+
+    /// Deprecated: use `show <name>`
+    #[structopt(short, long, hidden = true)]
     character: Option<String>,
 
-    /// Perform meta search for disadvantages of selected characters
-    #[structopt(short, long)]
+    /// Deprecated: use `meta <names>...`
+    #[structopt(short, long, hidden = true)]
     meta: Option<Vec<String>>,
 
-    /// List all characters
-    #[structopt(short, long)]
+    /// Deprecated: use `meta --by-class`
+    #[structopt(long, hidden = true)]
+    by_class: bool,
+
+    /// Deprecated: use `meta --strict` or `recommend --strict`
+    #[structopt(long, hidden = true)]
+    strict: bool,
+
+    /// Deprecated: use `list`
+    #[structopt(short, long, hidden = true)]
     list: bool,
+
+    /// Deprecated: use `counters <name>`
+    #[structopt(long, hidden = true)]
+    counters: Option<String>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Display the details of a character
+    Show {
+        /// Character name or alias
+        name: String,
+    },
+    /// List all characters
+    List,
+    /// Rank what the selected characters are weak to
+    Meta {
+        /// Character names or aliases
+        #[structopt(required = true)]
+        names: Vec<String>,
+
+        /// Group results by class
+        #[structopt(long)]
+        by_class: bool,
+
+        /// Fail instead of ignoring names that match no character
+        #[structopt(long)]
+        strict: bool,
+    },
+    /// Show which characters counter the given character
+    Counters {
+        /// Character name or alias
+        name: String,
+    },
+    /// Browse and edit the data with a menu (the default without a command)
+    Interactive,
+    /// Check the data file for dangling references, empty strings, duplicates and alias collisions
+    Validate,
+    /// Convert character data between JSON, YAML and TOML
+    Convert {
+        /// File to read
+        source: String,
+
+        /// File to write
+        destination: String,
+
+        /// Format of the source (defaults to its extension)
+        #[structopt(long, possible_values = &DataFormat::VARIANTS)]
+        from: Option<DataFormat>,
+
+        /// Format of the destination (defaults to its extension)
+        #[structopt(long, possible_values = &DataFormat::VARIANTS)]
+        to: Option<DataFormat>,
+    },
+    /// Rewrite the data file in the latest schema version, keeping the original as <file>.bak
+    Migrate,
+    /// Recommend a team that counters the given enemy lineup
+    Recommend {
+        /// Enemy character names or aliases
+        #[structopt(required = true)]
+        enemies: Vec<String>,
+
+        /// Number of characters in the recommended team
+        #[structopt(short = "n", long, default_value = "4")]
+        size: usize,
+
+        /// Fail instead of ignoring enemy names that match no character
+        #[structopt(long)]
+        strict: bool,
+    },
+    /// Simulate a pick/ban draft with suggestions after every step
+    Draft {
+        /// Bans per side before picking starts
+        #[structopt(long, default_value = "2")]
+        pre_bans: usize,
+
+        /// Picks per side, in snake order
+        #[structopt(long, default_value = "5")]
+        picks: usize,
+
+        /// Bans per side on the opponent's picks after picking ends
+        #[structopt(long, default_value = "1")]
+        final_bans: usize,
+    },
+    /// Add a character to the data file
+    Add {
+        /// Name of the new character
+        name: String,
+
+        /// Alias of the character (repeatable)
+        #[structopt(long = "alias")]
+        aliases: Vec<String>,
+
+        /// Character or class it is strong against, as NAME or NAME:WEIGHT (repeatable)
+        #[structopt(long = "advantage")]
+        advantages: Vec<Matchup>,
+
+        /// Character or class it is weak against, as NAME or NAME:WEIGHT (repeatable)
+        #[structopt(long = "disadvantage")]
+        disadvantages: Vec<Matchup>,
+
+        #[structopt(long)]
+        class: Option<Class>,
+
+        #[structopt(long)]
+        element: Option<Element>,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+    /// Change the name, class or element of a character
+    Update {
+        /// Name or alias of the character
+        name: String,
+
+        /// New name
+        #[structopt(long)]
+        rename: Option<String>,
+
+        #[structopt(long)]
+        class: Option<Class>,
+
+        #[structopt(long)]
+        element: Option<Element>,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+    /// Remove a character from the data file
+    Remove {
+        /// Name or alias of the character
+        name: String,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+    /// Add or remove aliases
+    Alias(AliasCommand),
+    /// Add or remove advantages and disadvantages
+    Matchup(MatchupCommand),
+    /// Import characters from a spreadsheet
+    Import(ImportCommand),
+    /// Export characters for a spreadsheet
+    Export(ExportCommand),
+}
+
+#[derive(StructOpt, Debug)]
+struct EditOpts {
+    /// Save even if the edit introduces validation issues
+    #[structopt(long)]
+    force: bool,
+}
+
+#[derive(StructOpt, Debug)]
+enum AliasCommand {
+    /// Add an alias to a character
+    Add {
+        /// Name or alias of the character
+        name: String,
+        alias: String,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+    /// Remove an alias from a character
+    Remove {
+        /// Name or alias of the character
+        name: String,
+        alias: String,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
 }
 
-fn load_characters(file_path: &str) -> io::Result<Vec<Character>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let characters: Vec<Character> = from_reader(reader)?;
-    Ok(characters)
+#[derive(StructOpt, Debug)]
+enum MatchupCommand {
+    /// Add an entry to a character's advantages or disadvantages
+    Add {
+        /// Name or alias of the character
+        name: String,
+        /// "advantage" or "disadvantage"
+        list: MatchupList,
+        /// Character or class name
+        target: String,
+
+        /// Matchup strength, e.g. 3 for a hard counter
+        #[structopt(short, long, default_value = "1")]
+        weight: u32,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+    /// Remove an entry from a character's advantages or disadvantages
+    Remove {
+        /// Name or alias of the character
+        name: String,
+        /// "advantage" or "disadvantage"
+        list: MatchupList,
+        /// Character or class name, as written in the data file
+        target: String,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum ImportCommand {
+    /// Replace (or create) the data file with the rows of a CSV file, in the layout `export csv` writes
+    Csv {
+        /// CSV file to read
+        path: String,
+
+        /// Update characters with the same name and add new ones instead of replacing everything
+        #[structopt(long)]
+        merge: bool,
+
+        #[structopt(flatten)]
+        edit: EditOpts,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum ExportCommand {
+    /// Write the character table as CSV, with lists joined by "; "
+    Csv {
+        /// File to write (prints to stdout if omitted)
+        path: Option<String>,
+    },
+}
+
+/// Formats matchups, tagging those for which `from_overlay` is true.
+fn format_matchups(matchups: &[Matchup], from_overlay: impl Fn(&Matchup) -> bool) -> String {
+    matchups
+        .iter()
+        .filter(|m| !m.is_empty())
+        .map(|m| if from_overlay(m) { format!("{} {}", m, OVERLAY_TAG) } else { m.to_string() })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+const OVERLAY_TAG: &str = "[overlay]";
+
+fn suggestions<'a>(db: &'a CharacterDb, name_or_alias: &str) -> Vec<&'a str> {
+    db.suggest(name_or_alias, 3).iter().map(|c| c.name.as_str()).collect()
+}
+
+fn report_not_found(db: &CharacterDb, name_or_alias: &str) {
+    println!("Character not found.");
+    let suggestions = suggestions(db, name_or_alias);
+    if !suggestions.is_empty() {
+        println!("{}", format!("Did you mean {}?", suggestions.join(", ")).yellow());
+    }
+}
+
+/// Reports names that did not resolve.
+fn report_unknown(db: &CharacterDb, unknown: &[&str]) {
+    for name in unknown {
+        let suggestions = suggestions(db, name);
+        if suggestions.is_empty() {
+            eprintln!("{}", format!("Unknown character: {}", name).yellow());
+        } else {
+            eprintln!("{}", format!("Unknown character: {} (did you mean {}?)", name, suggestions.join(", ")).yellow());
+        }
+    }
 }
 
 fn display_character(character: &Character) {
+    display_character_with_overlay(character, None);
+}
+
+/// Displays a character, tagging values the overlay added (`changes`, resolved
+/// against `db`) and listing what it removed.
+fn display_character_with_overlay(character: &Character, overlay: Option<(&CharacterDb, &OverlayEntry)>) {
+    let empty = OverlayEntry::default();
+    let (db, changes) = match overlay {
+        Some((db, changes)) => (Some(db), changes),
+        None => (None, &empty),
+    };
+    let added = |list: MatchupList| {
+        move |matchup: &Matchup| {
+            db.is_some_and(|db| changes.add.matchups(list).iter().any(|m| db.resolve(m.target.raw()) == matchup.target))
+        }
+    };
+
     println!("{}", format!("Name: {}", character.name).cyan());
-    println!("{}", format!("Alias: {:?}", character.alias).cyan());
-    println!("{}", format!("Advantages: {:?}", character.advantages).magenta());
-    println!("{}", format!("Disadvantages: {:?}", character.disadvantages).red());
+    if changes.add.alias.is_empty() {
+        println!("{}", format!("Alias: {:?}", character.alias).cyan());
+    } else {
+        let aliases: Vec<String> = character
+            .alias
+            .iter()
+            .map(|a| if changes.add.alias.contains(a) { format!("{:?} {}", a, OVERLAY_TAG) } else { format!("{:?}", a) })
+            .collect();
+        println!("{}", format!("Alias: [{}]", aliases.join(", ")).cyan());
+    }
+    if let Some(class) = character.class {
+        println!("{}", format!("Class: {}", class).cyan());
+    }
+    if let Some(element) = character.element {
+        println!("{}", format!("Element: {}", element).cyan());
+    }
+    let advantages = format_matchups(&character.advantages, added(MatchupList::Advantages));
+    println!("{}", format!("Advantages: {}", advantages).magenta());
+    let disadvantages = format_matchups(&character.disadvantages, added(MatchupList::Disadvantages));
+    println!("{}", format!("Disadvantages: {}", disadvantages).red());
+
+    let removed = &changes.remove;
+    if !removed.is_empty() {
+        let mut parts = Vec::new();
+        if !removed.alias.is_empty() {
+            parts.push(format!("alias {}", removed.alias.join(", ")));
+        }
+        for (label, matchups) in [("advantages", &removed.advantages), ("disadvantages", &removed.disadvantages)] {
+            if !matchups.is_empty() {
+                let names: Vec<&str> = matchups.iter().map(|m| m.target.raw()).collect();
+                parts.push(format!("{} {}", label, names.join(", ")));
+            }
+        }
+        println!("{}", format!("Removed by overlay: {}", parts.join("; ")).dimmed());
+    }
 }
 
-fn display_character_list(characters: &Vec<Character>) {
+fn display_character_list(characters: &[Character]) {
     for character in characters {
-        println!("{} ({:?})", character.name.cyan(), character.alias);
+        match character.class {
+            Some(class) => println!("{} [{}] ({:?})", character.name.cyan(), class, character.alias),
+            None => println!("{} ({:?})", character.name.cyan(), character.alias),
+        }
     }
 }
 
-fn find_disadvantages(characters: &Vec<Character>, selected: Vec<&str>) -> Vec<String> {
-    let mut disadvantage_counts: HashMap<String, usize> = HashMap::new();
+/// The --roster file and how to apply it to results.
+struct RosterView {
+    roster: Roster,
+    owned_only: bool,
+}
 
-    for character in characters {
-        if selected.contains(&character.name.as_str()) || character.alias.iter().any(|alias| selected.contains(&alias.as_str())) {
-            for disadvantage in &character.disadvantages {
-                *disadvantage_counts.entry(disadvantage.clone()).or_insert(0) += 1;
+impl RosterView {
+    fn apply_threats(&self, threats: &mut Vec<Threat>) {
+        if self.owned_only {
+            self.roster.retain_owned_threats(threats);
+        } else {
+            self.roster.mark_threats(threats);
+        }
+    }
+
+    fn apply_counters(&self, counters: &mut Vec<Counter>) {
+        if self.owned_only {
+            self.roster.retain_owned_counters(counters);
+        } else {
+            self.roster.mark_counters(counters);
+        }
+    }
+
+    /// Characters that may be suggested for our side.
+    fn candidates<'a>(&self, db: &'a CharacterDb) -> Vec<&'a Character> {
+        db.characters().iter().filter(|c| !self.owned_only || self.roster.owns(c)).collect()
+    }
+
+    fn label(&self, character: &Character) -> String {
+        match self.roster.entry(character) {
+            Some(RosterEntry { investment: Some(investment), .. }) => format!(" [investment {}]", investment),
+            Some(_) => String::new(),
+            None => " (not owned)".to_string(),
+        }
+    }
+}
+
+fn owned_label(owned: Option<bool>) -> ColoredString {
+    match owned {
+        Some(false) => " (not owned)".dimmed(),
+        _ => "".normal(),
+    }
+}
+
+fn display_threat(threat: &Threat) {
+    println!(
+        "{}{} {} {}",
+        threat.matchup,
+        owned_label(threat.owned),
+        format!("score {}", threat.score).yellow(),
+        format!("(counters {}: {})", threat.count(), threat.contributors.join(", ")).dimmed()
+    );
+}
+
+fn display_disadvantages(threats: &[Threat]) {
+    println!("Disadvantages for selected characters:");
+    for threat in threats {
+        display_threat(threat);
+    }
+}
+
+fn display_disadvantages_by_class(db: &CharacterDb, threats: &[Threat]) {
+    println!("Disadvantages for selected characters:");
+    for (class, group) in db.group_by_class(threats) {
+        let heading = class.map_or_else(|| "Unclassified".to_string(), |class| class.to_string());
+        println!("{}", format!("[{}]", heading).green());
+        for threat in group {
+            display_threat(threat);
+        }
+    }
+}
+
+fn display_counters(db: &CharacterDb, roster: Option<&RosterView>, name_or_alias: &str) {
+    match db.find_counters(name_or_alias) {
+        Some(mut counters) => {
+            if let Some(roster) = roster {
+                roster.apply_counters(&mut counters);
+            }
+            println!("Counters for {}:", name_or_alias);
+            for counter in counters {
+                println!(
+                    "{}{} {}",
+                    counter.matchup,
+                    owned_label(counter.owned),
+                    format!("score {}", counter.score).yellow()
+                );
             }
         }
+        None => report_not_found(db, name_or_alias),
+    }
+}
+
+fn format_scores(scores: &[(String, u32)]) -> String {
+    let scores: Vec<String> = scores.iter().map(|(name, score)| format!("{} ({})", name, score)).collect();
+    if scores.is_empty() {
+        "-".to_string()
+    } else {
+        scores.join(", ")
+    }
+}
+
+fn display_recommendation(db: &CharacterDb, roster: Option<&RosterView>, recommendation: &Recommendation) {
+    println!(
+        "{} {}",
+        "Recommended team:".green(),
+        format!(
+            "score {} (coverage {}, exposure {})",
+            recommendation.score(),
+            recommendation.coverage,
+            recommendation.exposure
+        )
+        .yellow()
+    );
+    for member in &recommendation.team {
+        let label = match (roster, db.find(&member.name)) {
+            (Some(roster), Some(character)) => roster.label(character),
+            _ => String::new(),
+        };
+        println!("{}{}", member.name.cyan(), label.dimmed());
+        println!("  {}", format!("Counters: {}", format_scores(&member.covers)).magenta());
+        println!("  {}", format!("Countered by: {}", format_scores(&member.exposed_to)).red());
+    }
+    if !recommendation.uncovered.is_empty() {
+        println!("{}", format!("Uncovered enemies: {}", recommendation.uncovered.join(", ")).red());
+    }
+}
+
+fn display_draft_side(draft: &Draft, roster: Option<&RosterView>, side: Side) {
+    let names = |characters: &[&Character]| -> String {
+        let names: Vec<&str> = characters.iter().map(|c| c.name.as_str()).collect();
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+    println!("{}", format!("[{}]", side).green());
+    println!("  Bans: {}", names(draft.pre_bans(side)));
+    println!("  {}", format!("Picks: {}", names(&draft.team(side))).cyan());
+    if !draft.final_bans(side).is_empty() {
+        println!("  {}", format!("Banned picks: {}", names(draft.final_bans(side))).red());
+    }
+    let mut threats = draft.threats(side);
+    if let Some(roster) = roster {
+        roster.roster.mark_threats(&mut threats);
+    }
+    if !threats.is_empty() {
+        println!("  Threats:");
+        for threat in threats.iter().take(5) {
+            print!("    ");
+            display_threat(threat);
+        }
+    }
+}
+
+/// Runs a draft; with a roster, suggestions for our side (Side 1) respect it.
+fn draft_mode(db: &CharacterDb, roster: Option<&RosterView>, format: DraftFormat) -> Result<(), DraftError> {
+    let mut draft = Draft::new(db, format)?;
+    while let Some(step) = draft.current() {
+        let (done, total) = draft.progress();
+        println!();
+        println!("{}", format!("Step {}/{}: {} {}", done + 1, total, step.side, step.action).green());
+        let ours = step.side == Side::First && step.action != Action::FinalBan;
+        let suggestions: Vec<String> = draft
+            .suggestions(db.characters().len())
+            .into_iter()
+            .filter_map(|(c, score)| match roster {
+                Some(roster) if ours && roster.owned_only && !roster.roster.owns(c) => None,
+                Some(roster) if ours => Some(format!("{}{} ({})", c.name, roster.label(c), score)),
+                _ => Some(format!("{} ({})", c.name, score)),
+            })
+            .take(5)
+            .collect();
+        if !suggestions.is_empty() {
+            println!("{}", format!("Suggested: {}", suggestions.join(", ")).yellow());
+        }
+        println!("Enter character name or alias (or \"quit\"):");
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).expect("Failed to read line") == 0 || input.trim() == "quit" {
+            return Ok(());
+        }
+        match draft.play(input.trim()) {
+            Ok(_) => {
+                display_draft_side(&draft, roster, Side::First);
+                display_draft_side(&draft, roster, Side::Second);
+            }
+            Err(DraftError::UnknownCharacter(name)) => report_not_found(db, &name),
+            Err(err) => println!("{}", err.to_string().red()),
+        }
+    }
+    println!();
+    println!("{}", "Draft finished.".green());
+    Ok(())
+}
+
+/// Reads one trimmed line from stdin, or `None` at end of input.
+fn read_line() -> Option<String> {
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line).expect("Failed to read line") {
+        0 => None,
+        _ => Some(line.trim().to_string()),
+    }
+}
+
+fn prompt(message: &str) -> Option<String> {
+    println!("{}", message);
+    read_line()
+}
+
+fn confirm(message: &str) -> bool {
+    prompt(&format!("{} [y/N]", message)).is_some_and(|answer| answer.eq_ignore_ascii_case("y"))
+}
+
+fn split_list(input: &str) -> Vec<String> {
+    input.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+fn parse_matchups(input: &str) -> Result<Vec<Matchup>, String> {
+    split_list(input).iter().map(|spec| spec.parse()).collect()
+}
+
+/// Asks for an optional value; empty input means "none".
+fn prompt_optional<T: std::str::FromStr<Err = String>>(message: &str) -> Option<Result<Option<T>, String>> {
+    let input = prompt(message)?;
+    Some(if input.is_empty() { Ok(None) } else { input.parse().map(Some) })
+}
+
+fn prompt_new_character() -> Option<Result<Character, String>> {
+    let name = prompt("Enter the new character's name:")?;
+    if name.is_empty() {
+        return Some(Err("the name must not be empty".to_string()));
     }
+    let alias = split_list(&prompt("Enter aliases (comma separated, may be empty):")?);
+    let advantages = prompt("Enter advantages as NAME or NAME:WEIGHT (comma separated, may be empty):")?;
+    let disadvantages = prompt("Enter disadvantages as NAME or NAME:WEIGHT (comma separated, may be empty):")?;
+    let class = prompt_optional("Enter class (empty for none):")?;
+    let element = prompt_optional("Enter element (empty for none):")?;
+    Some((|| {
+        Ok(Character {
+            name,
+            alias,
+            advantages: parse_matchups(&advantages)?,
+            disadvantages: parse_matchups(&disadvantages)?,
+            class: class?,
+            element: element?,
+        })
+    })())
+}
 
-    let mut disadvantages: Vec<(String, usize)> = disadvantage_counts.into_iter().collect();
+/// Edits aliases and matchups of one character; returns true if anything changed.
+fn edit_character_menu(editor: &mut Editor) -> bool {
+    let Some(name) = prompt("Enter character name or alias:") else {
+        return false;
+    };
+    let Some(character) = editor.get(&name) else {
+        println!("Character not found.");
+        return false;
+    };
+    let name = character.name.clone();
+    let mut changed = false;
+    loop {
+        // Resolve the edited entries so matchups show what they refer to
+        let db = CharacterDb::new(editor.characters().to_vec());
+        if let Some(character) = db.find(&name) {
+            display_character(character);
+        }
+        println!("{}", format!("Editing {}:", name).green());
+        println!("{}", "1. Add alias".cyan());
+        println!("{}", "2. Remove alias".cyan());
+        println!("{}", "3. Add advantage".cyan());
+        println!("{}", "4. Remove advantage".cyan());
+        println!("{}", "5. Add disadvantage".cyan());
+        println!("{}", "6. Remove disadvantage".cyan());
+        println!("{}", "7. Back".red());
 
-    // Sort by occurrence count in descending order
-    disadvantages.sort_by(|a, b| b.1.cmp(&a.1));
+        let Some(choice) = read_line() else {
+            return changed;
+        };
+        let result = match choice.parse::<u32>().unwrap_or(0) {
+            choice @ (1 | 2) => {
+                let Some(alias) = prompt("Enter alias:") else {
+                    return changed;
+                };
+                if choice == 1 {
+                    editor.add_alias(&name, &alias)
+                } else {
+                    editor.remove_alias(&name, &alias)
+                }
+            }
+            choice @ 3..=6 => {
+                let list = if choice <= 4 { MatchupList::Advantages } else { MatchupList::Disadvantages };
+                let adding = choice % 2 == 1;
+                let message = if adding { "Enter NAME or NAME:WEIGHT:" } else { "Enter name as written in the list:" };
+                let Some(input) = prompt(message) else {
+                    return changed;
+                };
+                if adding {
+                    match input.parse::<Matchup>() {
+                        Ok(matchup) => editor.add_matchup(&name, list, matchup),
+                        Err(err) => {
+                            println!("{}", err.red());
+                            continue;
+                        }
+                    }
+                } else {
+                    editor.remove_matchup(&name, list, &input)
+                }
+            }
+            7 => return changed,
+            _ => {
+                println!("Invalid option, try again.");
+                continue;
+            }
+        };
+        match result {
+            Ok(()) => changed = true,
+            Err(err) => println!("{}", err.to_string().red()),
+        }
+    }
+}
 
-    // Extract only the character names from the sorted result
-    disadvantages.into_iter().map(|(name, _)| name).collect()
+/// Saves after confirmation, showing any validation issues introduced since
+/// `baseline`. Returns true if the file was written.
+fn save_interactive(file: &str, editor: &Editor, baseline: &[Character]) -> bool {
+    let issues = new_issues(baseline, editor.characters());
+    for issue in &issues {
+        println!("{}", issue.to_string().yellow());
+    }
+    let question = if issues.is_empty() {
+        format!("Save changes to {}?", file)
+    } else {
+        format!("Save changes to {} despite {} new issue(s)?", file, issues.len())
+    };
+    if !confirm(&question) {
+        println!("Not saved.");
+        return false;
+    }
+    match editor.save(file) {
+        Ok(()) => {
+            println!("{}", format!("Saved {}", file).green());
+            true
+        }
+        Err(err) => {
+            println!("{}", format!("Error: {}", err).red());
+            false
+        }
+    }
 }
 
-fn interactive_mode(characters: &Vec<Character>) {
+/// Runs the menu. Without a single data `file` to save to, edits stay in memory.
+/// The overlay is applied to what is shown but never saved.
+fn interactive_mode(
+    file: Option<&str>,
+    mut editor: Editor,
+    overlay: Option<&Overlay>,
+    affinity: AffinityTable,
+    roster: Option<&RosterView>,
+) -> character_picker::Result<()> {
+    let (characters, mut overlay_report) = overlaid(editor.characters().to_vec(), overlay);
+    let mut db = CharacterDb::new(characters).with_affinity(affinity.clone());
+    let mut baseline = editor.characters().to_vec();
+    let mut unsaved = false;
+
     loop {
         println!("{}", "Select an option:".green());
         println!("{}", "1. Display character details".cyan());
         println!("{}", "2. Meta search for disadvantages".cyan());
         println!("{}", "3. List all characters".cyan());
-        println!("{}", "4. Exit".red());
+        println!("{}", "4. Find counters for a character".cyan());
+        println!("{}", "5. Draft simulator".cyan());
+        println!("{}", "6. Add a character".cyan());
+        println!("{}", "7. Edit a character".cyan());
+        println!("{}", "8. Save changes".cyan());
+        println!("{}", "9. Exit".red());
 
-        let mut choice = String::new();
-        io::stdin().read_line(&mut choice).expect("Failed to read line");
-        let choice = choice.trim().parse::<u32>().unwrap_or(0);
+        let exit_question = "You have unsaved changes. Exit without saving?".yellow().to_string();
+        let Some(choice) = read_line() else {
+            // End of input exits like option 9; the question can only be
+            // answered if input is still open, so the edits would be lost
+            if unsaved && !confirm(&exit_question) {
+                let message = "input closed with unsaved changes; they were not saved";
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, message).into());
+            }
+            break;
+        };
+        let choice = choice.parse::<u32>().unwrap_or(0);
 
         match choice {
             1 => {
                 println!("Enter character name or alias:");
                 let mut name_or_alias = String::new();
                 io::stdin().read_line(&mut name_or_alias).expect("Failed to read line");
-                let name_or_alias = name_or_alias.trim();
-                if let Some(character) = characters.iter().find(|c| c.name == name_or_alias || c.alias.contains(&name_or_alias.to_string())) {
-                    display_character(character);
+                if let Some(character) = db.find(name_or_alias.trim()) {
+                    let changes = overlay_report.get(&character.name).map(|changes| (&db, changes));
+                    display_character_with_overlay(character, changes);
                 } else {
-                    println!("Character not found.");
+                    report_not_found(&db, name_or_alias.trim());
                 }
             }
             2 => {
@@ -103,48 +803,483 @@ fn interactive_mode(characters: &Vec<Character>) {
                 let mut names_or_aliases = String::new();
                 io::stdin().read_line(&mut names_or_aliases).expect("Failed to read line");
                 let names_or_aliases: Vec<&str> = names_or_aliases.trim().split(',').map(|s| s.trim()).collect();
-                let disadvantages = find_disadvantages(&characters, names_or_aliases);
-                println!("Disadvantages for selected characters:");
-                for disadvantage in disadvantages {
-                    println!("{}", disadvantage);
+                let selection = db.select(&names_or_aliases);
+                report_unknown(&db, &selection.unknown);
+                let mut threats = db.find_disadvantages_of(&selection.characters);
+                if let Some(roster) = roster {
+                    roster.apply_threats(&mut threats);
                 }
+                display_disadvantages(&threats);
             }
             3 => {
-                display_character_list(&characters);
+                display_character_list(db.characters());
+            }
+            4 => {
+                println!("Enter character name or alias:");
+                let mut name_or_alias = String::new();
+                io::stdin().read_line(&mut name_or_alias).expect("Failed to read line");
+                display_counters(&db, roster, name_or_alias.trim());
+            }
+            5 => {
+                if let Err(err) = draft_mode(&db, roster, DraftFormat::default()) {
+                    println!("{}", err.to_string().red());
+                }
+            }
+            6 | 7 => {
+                let changed = if choice == 6 {
+                    match prompt_new_character() {
+                        Some(Ok(character)) => match editor.add(character) {
+                            Ok(()) => true,
+                            Err(err) => {
+                                println!("{}", err.to_string().red());
+                                false
+                            }
+                        },
+                        Some(Err(err)) => {
+                            println!("{}", err.red());
+                            false
+                        }
+                        None => false,
+                    }
+                } else {
+                    edit_character_menu(&mut editor)
+                };
+                if changed {
+                    unsaved = true;
+                    let (characters, report) = overlaid(editor.characters().to_vec(), overlay);
+                    db = CharacterDb::new(characters).with_affinity(affinity.clone());
+                    overlay_report = report;
+                    println!("{}", "Changes are not saved yet; use option 8 to save.".yellow());
+                }
+            }
+            8 => {
+                let Some(file) = file else {
+                    println!("{}", format!("Error: {}", Error::NotEditable).red());
+                    continue;
+                };
+                if !unsaved {
+                    println!("No unsaved changes.");
+                } else if save_interactive(file, &editor, &baseline) {
+                    baseline = editor.characters().to_vec();
+                    unsaved = false;
+                }
+            }
+            9 => {
+                if !unsaved || confirm(&exit_question) {
+                    break;
+                }
             }
-            4 => break,
             _ => println!("Invalid option, try again."),
         }
     }
+    Ok(())
 }
 
-fn main() -> io::Result<()> {
-    let opt = Opt::from_args();
-    let characters = load_characters(&opt.file)?;
+fn run_convert(
+    source: &str,
+    destination: &str,
+    from: Option<DataFormat>,
+    to: Option<DataFormat>,
+) -> character_picker::Result<()> {
+    let to = to.unwrap_or_else(|| DataFormat::from_path(destination));
+    let characters = match from {
+        Some(from) => load_characters_as(source, from)?,
+        None => load_characters(source)?,
+    };
+    save_characters_as(destination, &characters, to)?;
+    println!("{}", format!("Converted {} characters from {} to {}", characters.len(), source, destination).green());
+    Ok(())
+}
 
-    if opt.character.is_none() && opt.meta.is_none() && !opt.list {
-        interactive_mode(&characters);
-    } else {
-        if let Some(name_or_alias) = opt.character {
-            if let Some(character) = characters.iter().find(|c| c.name == name_or_alias || c.alias.contains(&name_or_alias)) {
-                display_character(character);
-            } else {
-                println!("Character not found.");
-            }
+fn run_migrate(file: &str) -> character_picker::Result<()> {
+    let migration = migrate_file(file)?;
+    match migration.backup {
+        Some(backup) => println!(
+            "{}",
+            format!(
+                "Migrated {} from schema version {} to {} (original saved as {})",
+                file,
+                migration.from,
+                SCHEMA_VERSION,
+                backup.display()
+            )
+            .green()
+        ),
+        None => println!("{}", format!("{} is already at schema version {}", file, SCHEMA_VERSION).green()),
+    }
+    Ok(())
+}
+
+fn run_import(file: &str, path: &str, merge: bool, force: bool) -> character_picker::Result<()> {
+    let import = load_csv(path)?;
+    // Without --merge the data file is replaced, so it need not exist yet
+    let mut editor = match Editor::load(file) {
+        Err(Error::FileNotFound(_)) if !merge => Editor::new(Vec::new()),
+        result => result?,
+    };
+    let before = editor.characters().to_vec();
+    let mut characters = if merge { before.clone() } else { Vec::new() };
+    for (_, character) in &import.characters {
+        match characters.iter_mut().find(|c| c.name == character.name) {
+            Some(existing) => *existing = character.clone(),
+            None => characters.push(character.clone()),
+        }
+    }
+    editor = Editor::new(characters).with_version(editor.version());
+
+    for error in &import.errors {
+        eprintln!("{}", format!("{}: {}", path, error).red());
+    }
+    for issue in validate(editor.characters(), None) {
+        if let Some(row) = import.row_of(&issue.character) {
+            eprintln!("{}", format!("{}: row {} ({}): {}", path, row, issue.character, issue.kind).yellow());
+        }
+    }
+    let issues = new_issues(&before, editor.characters());
+    let problems = import.errors.len() + issues.len();
+    if problems > 0 && !force {
+        eprintln!("{}", "The import has invalid rows or introduces validation issues; not saved (use --force to save anyway)".yellow());
+        return Err(Error::Validation(problems));
+    }
+    editor.save(file)?;
+    println!("{}", format!("Imported {} characters from {}", import.characters.len(), path).green());
+    Ok(())
+}
+
+fn run_export(opt: &Opt, path: Option<&str>) -> character_picker::Result<()> {
+    let characters = load_sources(opt)?;
+    let characters: Vec<&Character> = characters.iter().collect();
+    let csv = render_characters(&characters, OutputFormat::Csv).expect("CSV is not text output");
+    match path {
+        Some(path) => {
+            std::fs::write(path, csv)?;
+            println!("{}", format!("Exported {} characters to {}", characters.len(), path).green());
+        }
+        None => print!("{}", csv),
+    }
+    Ok(())
+}
+
+fn run_validate(opt: &Opt) -> character_picker::Result<()> {
+    // Positions only make sense within a single file
+    let file = match opt.file.as_slice() {
+        [file] if !Path::new(file).is_dir() => Some(file.as_str()),
+        _ => None,
+    };
+    let issues = match file {
+        Some(file) => validate_file(file)?,
+        None => validate(&load_sources(opt)?, None),
+    };
+    if issues.is_empty() {
+        println!("{}", format!("{}: no issues found", opt.file.join(", ")).green());
+        return Ok(());
+    }
+    for issue in &issues {
+        // `file:line:column: issue` with a position, `file: issue` without
+        match (file, issue.position) {
+            (Some(file), Some(_)) => println!("{}:{}", file, issue.to_string().red()),
+            (Some(file), None) => println!("{}: {}", file, issue.to_string().red()),
+            (None, _) => println!("{}", issue.to_string().red()),
+        }
+    }
+    Err(Error::Validation(issues.len()))
+}
+
+/// Applies an edit command to `editor`; returns its options and a success message.
+fn apply_edit(editor: &mut Editor, command: Command) -> (EditOpts, Result<String, EditError>) {
+    let (edit, result) = match command {
+        Command::Add { name, aliases, advantages, disadvantages, class, element, edit } => {
+            let character =
+                Character { name: name.clone(), alias: aliases, advantages, disadvantages, class, element };
+            (edit, editor.add(character).map(|_| format!("Added {}", name)))
+        }
+        Command::Update { name, rename, class, element, edit } => {
+            let result = editor.get_mut(&name).map(|character| {
+                character.class = class.or(character.class);
+                character.element = element.or(character.element);
+                character.name.clone()
+            });
+            let result = match (result, rename) {
+                (Ok(current), Some(new_name)) => editor.rename(&current, &new_name).map(|_| new_name),
+                (result, _) => result,
+            };
+            (edit, result.map(|name| format!("Updated {}", name)))
+        }
+        Command::Remove { name, edit } => (edit, editor.remove(&name).map(|c| format!("Removed {}", c.name))),
+        Command::Alias(AliasCommand::Add { name, alias, edit }) => {
+            (edit, editor.add_alias(&name, &alias).map(|_| format!("Added alias {} to {}", alias, name)))
+        }
+        Command::Alias(AliasCommand::Remove { name, alias, edit }) => {
+            (edit, editor.remove_alias(&name, &alias).map(|_| format!("Removed alias {} from {}", alias, name)))
+        }
+        Command::Matchup(MatchupCommand::Add { name, list, target, weight, edit }) => {
+            let matchup = Matchup::new(MatchupRef::Unresolved(target.clone()), weight);
+            let result = editor.add_matchup(&name, list, matchup);
+            (edit, result.map(|_| format!("Added {} to the {} of {}", target, list, name)))
+        }
+        Command::Matchup(MatchupCommand::Remove { name, list, target, edit }) => {
+            let result = editor.remove_matchup(&name, list, &target);
+            (edit, result.map(|_| format!("Removed {} from the {} of {}", target, list, name)))
+        }
+        _ => unreachable!("not an edit command"),
+    };
+    (edit, result)
+}
+
+fn is_edit(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Add { .. } | Command::Update { .. } | Command::Remove { .. } | Command::Alias(_) | Command::Matchup(_)
+    )
+}
+
+/// Runs an edit command against the data file.
+fn run_edit(file: &str, command: Command) -> character_picker::Result<()> {
+    let mut editor = Editor::load(file)?;
+    let before = editor.characters().to_vec();
+    let (edit, result) = apply_edit(&mut editor, command);
+    let message = result?;
+
+    let issues = new_issues(&before, editor.characters());
+    for issue in &issues {
+        eprintln!("{}", issue.to_string().yellow());
+    }
+    if !issues.is_empty() && !edit.force {
+        eprintln!("{}", "The edit introduces validation issues; not saved (use --force to save anyway)".yellow());
+        return Err(Error::Validation(issues.len()));
+    }
+    editor.save(file)?;
+    println!("{}", message.green());
+    Ok(())
+}
+
+/// The data file to edit: `--file` must name exactly one file, other than
+/// standard input.
+fn single_file(opt: &Opt) -> character_picker::Result<&str> {
+    match opt.file.as_slice() {
+        [file] if file != STDIN && !Path::new(file).is_dir() => Ok(file),
+        _ => Err(Error::NotEditable),
+    }
+}
+
+/// Loads and merges every `--file`, warning about overridden definitions.
+fn load_sources(opt: &Opt) -> character_picker::Result<Vec<Character>> {
+    let merged = load_merged(&opt.file, opt.on_conflict)?;
+    for conflict in &merged.conflicts {
+        eprintln!("{}", format!("Warning: {}; using the latter", conflict).yellow());
+    }
+    Ok(merged.characters)
+}
+
+/// Applies `overlay`, if any, to the characters.
+fn overlaid(characters: Vec<Character>, overlay: Option<&Overlay>) -> (Vec<Character>, OverlayReport) {
+    let Some(overlay) = overlay else {
+        return (characters, OverlayReport::default());
+    };
+    let mut editor = Editor::new(characters);
+    let report = overlay.apply(&mut editor);
+    (editor.into_characters(), report)
+}
+
+fn load_db(
+    opt: &Opt,
+    characters: Vec<Character>,
+    overlay: Option<&Overlay>,
+) -> character_picker::Result<(CharacterDb, OverlayReport)> {
+    let (characters, report) = overlaid(characters, overlay);
+    for err in &report.skipped {
+        eprintln!("{}", format!("Warning: overlay change not applied: {}", err).yellow());
+    }
+    let mut db = CharacterDb::new(characters);
+    if let Some(path) = &opt.affinity {
+        db = db.with_affinity(AffinityTable::load(path)?);
+    }
+    Ok((db, report))
+}
+
+fn load_roster(opt: &Opt, db: &CharacterDb) -> character_picker::Result<Option<RosterView>> {
+    let Some(path) = &opt.roster else {
+        return Ok(None);
+    };
+    let mut roster = Roster::load(path)?;
+    for name in roster.resolve(db) {
+        eprintln!("{}", format!("Warning: roster entry matches no character: {}", name).yellow());
+    }
+    Ok(Some(RosterView { roster, owned_only: opt.owned_only }))
+}
+
+/// Translates the deprecated top-level flags into commands, in the order
+/// they used to run.
+fn legacy_commands(opt: &mut Opt) -> Vec<Command> {
+    let mut commands = Vec::new();
+    let deprecated = |flag: &str, replacement: &str| {
+        eprintln!("{}", format!("Warning: {} is deprecated; use `{}` instead", flag, replacement).yellow());
+    };
+    if let Some(name) = opt.character.take() {
+        deprecated("--character", "show");
+        commands.push(Command::Show { name });
+    }
+    if let Some(names) = opt.meta.take() {
+        deprecated("--meta", "meta");
+        commands.push(Command::Meta { names, by_class: opt.by_class, strict: opt.strict });
+    }
+    if opt.list {
+        deprecated("--list", "list");
+        commands.push(Command::List);
+    }
+    if let Some(name) = opt.counters.take() {
+        deprecated("--counters", "counters");
+        commands.push(Command::Counters { name });
+    }
+    commands
+}
+
+/// Reports unresolved names; in strict mode the last one is returned as the error.
+fn check_unknown(db: &CharacterDb, unknown: &[&str], strict: bool) -> character_picker::Result<()> {
+    match unknown.split_last() {
+        Some((last, rest)) if strict => {
+            report_unknown(db, rest);
+            Err(db.not_found(last))
         }
+        _ => {
+            report_unknown(db, unknown);
+            Ok(())
+        }
+    }
+}
 
-        if let Some(selected) = opt.meta {
-            let disadvantages = find_disadvantages(&characters, selected.iter().map(String::as_str).collect());
-            println!("Disadvantages for selected characters:");
-            for disadvantage in disadvantages {
-                println!("{}", disadvantage);
+/// Runs a command that reads the database.
+fn run_query(
+    db: &CharacterDb,
+    overlay: &OverlayReport,
+    roster: Option<&RosterView>,
+    output: OutputFormat,
+    command: Command,
+) -> character_picker::Result<()> {
+    match command {
+        Command::Show { name } => {
+            let character = db.get(&name)?;
+            match render_character(character, output) {
+                Some(rendered) => print!("{}", rendered),
+                None => display_character_with_overlay(character, overlay.get(&character.name).map(|c| (db, c))),
+            }
+        }
+        Command::List => {
+            let characters: Vec<&Character> = db.characters().iter().collect();
+            match render_characters(&characters, output) {
+                Some(rendered) => print!("{}", rendered),
+                None => display_character_list(db.characters()),
+            }
+        }
+        Command::Meta { names, by_class, strict } => {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let selection = db.select(&names);
+            check_unknown(db, &selection.unknown, strict)?;
+            let mut threats = db.find_disadvantages_of(&selection.characters);
+            if let Some(roster) = roster {
+                roster.apply_threats(&mut threats);
+            }
+            if by_class {
+                threats = db.group_by_class(&threats).into_iter().flat_map(|(_, group)| group).cloned().collect();
+            }
+            match render_threats(&threats, output) {
+                Some(rendered) => print!("{}", rendered),
+                None if by_class => display_disadvantages_by_class(db, &threats),
+                None => display_disadvantages(&threats),
             }
         }
+        Command::Counters { name } => {
+            db.get(&name)?;
+            display_counters(db, roster, &name);
+        }
+        Command::Recommend { enemies, size, strict } => {
+            let enemies: Vec<&str> = enemies.iter().map(String::as_str).collect();
+            let selection = db.select(&enemies);
+            // Without a known enemy any team would do, so unknown names are fatal then
+            check_unknown(db, &selection.unknown, strict || selection.characters.is_empty())?;
+            if selection.characters.is_empty() {
+                println!("{}", "No enemies given; nothing to recommend a team against.".yellow());
+                return Ok(());
+            }
+            let recommendation = match roster {
+                Some(roster) => db.recommend_from(&roster.candidates(db), &selection.characters, size),
+                None => db.recommend(&selection.characters, size),
+            };
+            display_recommendation(db, roster, &recommendation);
+        }
+        Command::Draft { pre_bans, picks, final_bans } => {
+            draft_mode(db, roster, DraftFormat { pre_bans, picks, final_bans })?
+        }
+        _ => unreachable!("not a query command"),
+    }
+    Ok(())
+}
 
-        if opt.list {
-            display_character_list(&characters);
+/// The name of `command` if it reads its input from standard input.
+fn reads_stdin(command: &Command) -> Option<&'static str> {
+    match command {
+        Command::Interactive => Some("interactive mode"),
+        Command::Draft { .. } => Some("draft"),
+        _ => None,
+    }
+}
+
+fn run(mut opt: Opt) -> character_picker::Result<()> {
+    let commands = match opt.command.take() {
+        Some(command) => vec![command],
+        None => match legacy_commands(&mut opt) {
+            commands if commands.is_empty() => vec![Command::Interactive],
+            commands => commands,
+        },
+    };
+
+    match commands.first() {
+        Some(Command::Validate) => return run_validate(&opt),
+        Some(Command::Convert { source, destination, from, to }) => return run_convert(source, destination, *from, *to),
+        Some(Command::Migrate) => return run_migrate(single_file(&opt)?),
+        Some(Command::Export(ExportCommand::Csv { path })) => return run_export(&opt, path.as_deref()),
+        Some(Command::Import(ImportCommand::Csv { path, merge, edit })) => {
+            return run_import(single_file(&opt)?, path, *merge, edit.force)
         }
+        Some(command) if is_edit(command) => {
+            let file = single_file(&opt)?;
+            return commands.into_iter().try_for_each(|command| run_edit(file, command));
+        }
+        _ => {}
     }
+    // The menu and the draft read their choices from standard input, so it cannot also hold the data
+    if opt.file.iter().any(|file| file == STDIN) {
+        if let Some(command) = commands.iter().find_map(reads_stdin) {
+            return Err(Error::StdinInUse(command));
+        }
+    }
+
+    let overlay = opt.overlay.as_ref().map(Overlay::load).transpose()?;
+    // Standard input can only be read once, so the sources are kept for interactive edits
+    let characters = load_sources(&opt)?;
+    let (db, overlay_report) = load_db(&opt, characters.clone(), overlay.as_ref())?;
+    let roster = load_roster(&opt, &db)?;
+    let roster = roster.as_ref();
 
+    for command in commands {
+        match command {
+            Command::Interactive => {
+                let file = single_file(&opt).ok();
+                let editor = match file {
+                    Some(file) => Editor::load(file)?,
+                    None => Editor::new(characters.clone()),
+                };
+                interactive_mode(file, editor, overlay.as_ref(), db.affinity().clone(), roster)?
+            }
+            command => run_query(&db, &overlay_report, roster, opt.output, command)?,
+        }
+    }
     Ok(())
 }
+
+fn main() {
+    if let Err(err) = run(Opt::from_args()) {
+        eprintln!("{}", format!("Error: {}", err).red());
+        process::exit(err.exit_code());
+    }
+}
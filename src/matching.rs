@@ -0,0 +1,158 @@
+/// Normalizes a name for comparison.
+///
+/// Applies the NFKC mappings that occur in names: width folding (full-width
+/// ASCII and half-width katakana become their standard forms), compatibility
+/// characters (ligatures, circled and parenthesized letters and digits, Roman
+/// numerals, super- and subscript digits, special spaces) and composition of
+/// kana with combining sound marks. It then lowercases and collapses runs of
+/// whitespace into a single space.
+pub fn normalize(s: &str) -> String {
+    let mut folded = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\u{FF01}'..='\u{FF5E}' => folded.push(char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)),
+            '\u{3000}' => folded.push(' '),
+            // Half-width and combining (semi-)voiced sound marks combine with the previous kana
+            '\u{FF9E}' | '\u{FF9F}' | '\u{3099}' | '\u{309A}' => {
+                let mark = if c == '\u{FF9E}' || c == '\u{3099}' { 1 } else { 2 };
+                match folded.pop() {
+                    Some(prev) => folded.push(combine_sound_mark(prev, mark)),
+                    None => folded.push(c),
+                }
+            }
+            '\u{FF61}'..='\u{FF9D}' => folded.push(halfwidth_katakana(c)),
+            c => match compatibility(c) {
+                Some(mapped) => folded.push_str(&mapped),
+                None => folded.push(c),
+            },
+        }
+    }
+    folded.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The NFKC replacement of a compatibility character outside the width forms.
+fn compatibility(c: char) -> Option<String> {
+    let offset = |base: char| c as u32 - base as u32;
+    let digit = |n: u32| char::from_digit(n, 10).map(String::from);
+    match c {
+        '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' => Some(" ".to_string()),
+        '\u{00B2}' | '\u{00B3}' => digit(offset('\u{00B0}')),
+        '\u{00B9}' => digit(1),
+        '\u{2070}' | '\u{2074}'..='\u{2079}' => digit(offset('\u{2070}')),
+        '\u{2080}'..='\u{2089}' => digit(offset('\u{2080}')),
+        '\u{FB00}'..='\u{FB06}' => {
+            let ligatures = ["ff", "fi", "fl", "ffi", "ffl", "st", "st"];
+            Some(ligatures[offset('\u{FB00}') as usize].to_string())
+        }
+        '\u{2160}'..='\u{217F}' => {
+            let numerals = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII", "L", "C", "D", "M"];
+            let numeral = numerals[offset('\u{2160}') as usize % 16];
+            Some(if c >= '\u{2170}' { numeral.to_lowercase() } else { numeral.to_string() })
+        }
+        '\u{2460}'..='\u{2473}' => Some((offset('\u{2460}') + 1).to_string()),
+        '\u{2474}'..='\u{2487}' => Some(format!("({})", offset('\u{2474}') + 1)),
+        '\u{2488}'..='\u{249B}' => Some(format!("{}.", offset('\u{2488}') + 1)),
+        '\u{249C}'..='\u{24B5}' => char::from_u32('a' as u32 + offset('\u{249C}')).map(|l| format!("({})", l)),
+        '\u{24B6}'..='\u{24CF}' => char::from_u32('A' as u32 + offset('\u{24B6}')).map(String::from),
+        '\u{24D0}'..='\u{24E9}' => char::from_u32('a' as u32 + offset('\u{24D0}')).map(String::from),
+        '\u{24EA}' => digit(0),
+        '\u{3251}'..='\u{325F}' => Some((offset('\u{3251}') + 21).to_string()),
+        '\u{32B1}'..='\u{32BF}' => Some((offset('\u{32B1}') + 36).to_string()),
+        _ => None,
+    }
+}
+
+/// Levenshtein distance between two strings, counted in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How far apart `input` and `candidate` are, or `None` if they are too different
+/// to be worth suggesting. Both must already be normalized.
+pub(crate) fn suggestion_distance(input: &str, candidate: &str) -> Option<usize> {
+    if input.is_empty() {
+        return None;
+    }
+    if candidate.contains(input) || input.contains(candidate) {
+        return Some(1);
+    }
+    let distance = edit_distance(input, candidate);
+    let threshold = (input.chars().count() / 3).max(2);
+    (distance <= threshold).then_some(distance)
+}
+
+const HALFWIDTH_KATAKANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+fn halfwidth_katakana(c: char) -> char {
+    let index = (c as u32 - 0xFF61) as usize;
+    HALFWIDTH_KATAKANA.chars().nth(index).unwrap_or(c)
+}
+
+fn combine_sound_mark(kana: char, mark: u32) -> char {
+    let voiced = "カキクケコサシスセソタチツテトハヒフヘホかきくけこさしすせそたちつてとはひふへほ";
+    let semi_voiced = "ハヒフヘホはひふへほ";
+    match (kana, mark) {
+        ('ウ', 1) => 'ヴ',
+        ('う', 1) => 'ゔ',
+        (k, 1) if voiced.contains(k) => char::from_u32(k as u32 + 1).unwrap_or(k),
+        (k, 2) if semi_voiced.contains(k) => char::from_u32(k as u32 + 2).unwrap_or(k),
+        (k, _) => k,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_case_width_and_whitespace() {
+        assert_eq!(normalize("  Abyssal\u{3000}  YUFINE "), "abyssal yufine");
+        assert_eq!(normalize("Ａ．Ｙｕｆｉｎｅ"), "a.yufine");
+        assert_eq!(normalize("ｳﾞｨｵﾚﾀ"), "ヴィオレタ");
+        assert_eq!(normalize("ﾊﾟﾍﾞﾙ"), "パベル");
+    }
+
+    #[test]
+    fn maps_compatibility_characters() {
+        assert_eq!(normalize("ﬁre ﬀ"), "fire ff");
+        assert_eq!(normalize("①⑳㉑㊿"), "1202150");
+        assert_eq!(normalize("⑴⒈⒜"), "(1)1.(a)");
+        assert_eq!(normalize("Ⓐⓩ⓪"), "az0");
+        assert_eq!(normalize("Ⅻⅳ"), "xiiiv");
+        assert_eq!(normalize("x²₃⁰"), "x230");
+        assert_eq!(normalize("a\u{00A0}b\u{2003}c"), "a b c");
+    }
+
+    #[test]
+    fn composes_combining_sound_marks() {
+        assert_eq!(normalize("ウ\u{3099}ィオレタ"), "ヴィオレタ");
+        assert_eq!(normalize("か\u{3099}は\u{309A}"), "がぱ");
+    }
+
+    #[test]
+    fn leaves_other_text_alone() {
+        assert_eq!(normalize("深淵のユピネ"), "深淵のユピネ");
+        assert_eq!(normalize("é"), "é");
+    }
+
+    #[test]
+    fn suggests_only_close_names() {
+        assert_eq!(edit_distance("violeta", "violet"), 1);
+        assert_eq!(suggestion_distance("yufine", "a.yufine"), Some(1));
+        assert_eq!(suggestion_distance("violte", "violet"), Some(2));
+        assert_eq!(suggestion_distance("zzz", "violet"), None);
+        assert_eq!(suggestion_distance("", "violet"), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::class::Class;
+
+/// The character or class a matchup entry points at.
+///
+/// The data file stores every reference as a plain string. Entries are read as
+/// `Unresolved` and turned into `Character` or `Class` references when a
+/// `CharacterDb` is built; anything that matches neither stays `Unresolved`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MatchupRef {
+    Character(String),
+    Class(Class),
+    Unresolved(String),
+}
+
+impl MatchupRef {
+    /// The string this reference is stored as in the data file.
+    pub fn raw(&self) -> &str {
+        match self {
+            MatchupRef::Character(name) | MatchupRef::Unresolved(name) => name,
+            MatchupRef::Class(class) => class.as_str(),
+        }
+    }
+
+    /// Short lowercase label for the kind of reference.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MatchupRef::Character(_) => "character",
+            MatchupRef::Class(_) => "class",
+            MatchupRef::Unresolved(_) => "unknown",
+        }
+    }
+
+    /// True for the `""` placeholders some entries use to mean "none".
+    pub fn is_empty(&self) -> bool {
+        self.raw().trim().is_empty()
+    }
+}
+
+impl fmt::Display for MatchupRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchupRef::Character(name) => f.write_str(name),
+            MatchupRef::Class(class) => write!(f, "{} (class)", class),
+            MatchupRef::Unresolved(name) => write!(f, "{} (unknown)", name),
+        }
+    }
+}
+
+impl Serialize for MatchupRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.raw())
+    }
+}
+
+impl<'de> Deserialize<'de> for MatchupRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(MatchupRef::Unresolved)
+    }
+}
+
+/// Weight of a matchup entry written as a plain string.
+pub const DEFAULT_WEIGHT: u32 = 1;
+
+/// A weighted matchup entry, e.g. a hard counter (3) versus a soft one (1).
+///
+/// In the data file this is either a plain string, which gets
+/// `DEFAULT_WEIGHT`, or an object `{"name": "...", "weight": 3}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "RawMatchup", into = "RawMatchup")]
+pub struct Matchup {
+    pub target: MatchupRef,
+    pub weight: u32,
+}
+
+impl Matchup {
+    pub fn new(target: MatchupRef, weight: u32) -> Self {
+        Matchup { target, weight }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.target.is_empty()
+    }
+
+    /// Compact `name` or `name:weight` form used on the command line; the
+    /// inverse of [`Matchup::from_str`].
+    pub fn spec(&self) -> String {
+        if self.weight == DEFAULT_WEIGHT {
+            self.target.raw().to_string()
+        } else {
+            format!("{}:{}", self.target.raw(), self.weight)
+        }
+    }
+}
+
+impl FromStr for Matchup {
+    type Err = String;
+
+    /// Parses `name` or `name:weight`. The name is left unresolved.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.rsplit_once(':') {
+            Some((name, weight)) if weight.trim().chars().all(|c| c.is_ascii_digit()) && !weight.trim().is_empty() => {
+                let weight = weight.trim().parse().map_err(|_| format!("invalid weight in \"{}\"", s))?;
+                Ok(Matchup::new(MatchupRef::Unresolved(name.trim().to_string()), weight))
+            }
+            _ => Ok(Matchup::new(MatchupRef::Unresolved(s.to_string()), DEFAULT_WEIGHT)),
+        }
+    }
+}
+
+impl fmt::Display for Matchup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weight == DEFAULT_WEIGHT {
+            write!(f, "{}", self.target)
+        } else {
+            write!(f, "{} x{}", self.target, self.weight)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawMatchup {
+    Plain(MatchupRef),
+    Weighted {
+        name: MatchupRef,
+        #[serde(default = "default_weight")]
+        weight: u32,
+    },
+}
+
+fn default_weight() -> u32 {
+    DEFAULT_WEIGHT
+}
+
+impl From<RawMatchup> for Matchup {
+    fn from(raw: RawMatchup) -> Self {
+        match raw {
+            RawMatchup::Plain(target) => Matchup::new(target, DEFAULT_WEIGHT),
+            RawMatchup::Weighted { name, weight } => Matchup::new(name, weight),
+        }
+    }
+}
+
+impl From<Matchup> for RawMatchup {
+    fn from(matchup: Matchup) -> Self {
+        if matchup.weight == DEFAULT_WEIGHT {
+            RawMatchup::Plain(matchup.target)
+        } else {
+            RawMatchup::Weighted { name: matchup.target, weight: matchup.weight }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::character::Character;
+use crate::db::load_characters;
+use crate::error::{Error, Result};
+use crate::format::DataFormat;
+
+/// What to do when two data files define a character with the same name
+/// differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precedence {
+    /// The later file's definition replaces the earlier one.
+    #[default]
+    Override,
+    /// Fail with [`Error::Conflict`].
+    Error,
+}
+
+impl Precedence {
+    pub const VARIANTS: [&'static str; 2] = ["override", "error"];
+}
+
+impl FromStr for Precedence {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "override" => Ok(Precedence::Override),
+            "error" => Ok(Precedence::Error),
+            other => Err(format!("unknown precedence: {}", other)),
+        }
+    }
+}
+
+/// A character defined differently by two data files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub name: String,
+    pub earlier: PathBuf,
+    pub later: PathBuf,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is defined in both {} and {}", self.name, self.earlier.display(), self.later.display())
+    }
+}
+
+/// Characters merged from several data files.
+#[derive(Debug, Clone, Default)]
+pub struct Merged {
+    /// In order of first definition.
+    pub characters: Vec<Character>,
+    /// Conflicts resolved by [`Precedence::Override`].
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The data files `path` stands for: the file itself, or the JSON, YAML and
+/// TOML files directly inside a directory, sorted by file name.
+pub fn data_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let file = entry?.path();
+        let extension = file.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        if file.is_file() && extension.parse::<DataFormat>().is_ok() {
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Loads and merges files and directories of character data in order.
+/// Identical definitions of the same `name` in different files are merged
+/// silently; differing ones are resolved by `precedence`. Duplicates within
+/// one file are kept, for [`validate`](crate::validate) to report.
+pub fn load_merged<P: AsRef<Path>>(paths: &[P], precedence: Precedence) -> Result<Merged> {
+    let mut merged = Merged::default();
+    let mut origins: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        for file in data_files(path)? {
+            let earlier = merged.characters.len();
+            for character in load_characters(&file)? {
+                let Some(index) = merged.characters[..earlier].iter().position(|c| c.name == character.name) else {
+                    merged.characters.push(character);
+                    origins.push(file.clone());
+                    continue;
+                };
+                if merged.characters[index] == character {
+                    continue;
+                }
+                let conflict = Conflict { name: character.name.clone(), earlier: origins[index].clone(), later: file.clone() };
+                if precedence == Precedence::Error {
+                    return Err(Error::Conflict(conflict));
+                }
+                merged.characters[index] = character;
+                origins[index] = file.clone();
+                merged.conflicts.push(conflict);
+            }
+        }
+    }
+    Ok(merged)
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::character::Character;
+use crate::class::Class;
+use crate::db::Threat;
+use crate::matchup::{Matchup, DEFAULT_WEIGHT};
+
+/// How command results are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Colored, human-readable output.
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl OutputFormat {
+    pub const VARIANTS: [&'static str; 4] = ["text", "json", "csv", "markdown"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            other => Err(format!("unknown output format: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "markdown",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Serialize)]
+struct ThreatRow<'a> {
+    matchup: &'a str,
+    kind: &'static str,
+    class: Option<Class>,
+    score: u32,
+    count: usize,
+    contributors: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owned: Option<bool>,
+}
+
+impl<'a> From<&'a Threat> for ThreatRow<'a> {
+    fn from(threat: &'a Threat) -> Self {
+        ThreatRow {
+            matchup: threat.matchup.raw(),
+            kind: threat.matchup.kind(),
+            class: threat.class,
+            score: threat.score,
+            count: threat.count(),
+            contributors: &threat.contributors,
+            owned: threat.owned,
+        }
+    }
+}
+
+pub(crate) const CHARACTER_HEADER: [&str; 6] = ["name", "alias", "class", "element", "advantages", "disadvantages"];
+const THREAT_HEADER: [&str; 7] = ["matchup", "kind", "class", "score", "count", "contributors", "owned"];
+
+/// Renders characters in a machine-readable format. Returns `None` for `Text`,
+/// which the caller prints itself.
+pub fn render_characters(characters: &[&Character], format: OutputFormat) -> Option<String> {
+    match format {
+        OutputFormat::Text => None,
+        OutputFormat::Json => Some(to_json(&characters)),
+        OutputFormat::Csv => Some(csv_table(&CHARACTER_HEADER, characters.iter().map(|c| csv_character_cells(c)))),
+        OutputFormat::Markdown => Some(markdown_table(&CHARACTER_HEADER, characters.iter().map(|c| character_cells(c)))),
+    }
+}
+
+/// Renders a single character; JSON output is an object rather than an array.
+pub fn render_character(character: &Character, format: OutputFormat) -> Option<String> {
+    match format {
+        OutputFormat::Json => Some(to_json(character)),
+        _ => render_characters(&[character], format),
+    }
+}
+
+/// Renders meta search results in a machine-readable format. Returns `None` for `Text`.
+pub fn render_threats(threats: &[Threat], format: OutputFormat) -> Option<String> {
+    let rows: Vec<ThreatRow> = threats.iter().map(ThreatRow::from).collect();
+    let cells = || {
+        rows.iter().map(|row| {
+            vec![
+                row.matchup.to_string(),
+                row.kind.to_string(),
+                class_cell(row.class),
+                row.score.to_string(),
+                row.count.to_string(),
+                row.contributors.join(LIST_SEPARATOR),
+                row.owned.map(|owned| if owned { "yes" } else { "no" }).unwrap_or_default().to_string(),
+            ]
+        })
+    };
+    match format {
+        OutputFormat::Text => None,
+        OutputFormat::Json => Some(to_json(&rows)),
+        OutputFormat::Csv => Some(csv_table(&THREAT_HEADER, cells())),
+        OutputFormat::Markdown => Some(markdown_table(&THREAT_HEADER, cells())),
+    }
+}
+
+/// Separator used when a list is flattened into a single table cell.
+pub(crate) const LIST_SEPARATOR: &str = "; ";
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    let mut out = serde_json::to_string_pretty(value).expect("output rows always serialize");
+    out.push('\n');
+    out
+}
+
+fn class_cell(class: Option<Class>) -> String {
+    class.map(|class| class.to_string()).unwrap_or_default()
+}
+
+fn character_cells(character: &Character) -> Vec<String> {
+    let list = |matchups: &[Matchup]| {
+        matchups.iter().filter(|m| !m.is_empty()).map(Matchup::to_string).collect::<Vec<_>>().join(LIST_SEPARATOR)
+    };
+    vec![
+        character.name.clone(),
+        character.alias.join(LIST_SEPARATOR),
+        class_cell(character.class),
+        character.element.map(|element| element.to_string()).unwrap_or_default(),
+        list(&character.advantages),
+        list(&character.disadvantages),
+    ]
+}
+
+/// Like [`character_cells`], but with every alias and matchup entry kept and
+/// escaped (see [`csv_list_item`]) so that `import csv` reads them back unchanged.
+fn csv_character_cells(character: &Character) -> Vec<String> {
+    let list = |matchups: &[Matchup]| {
+        let items = matchups.iter().map(|m| match m.weight {
+            DEFAULT_WEIGHT => csv_list_item(m.target.raw(), true),
+            weight => format!("{}:{}", csv_list_item(m.target.raw(), true), weight),
+        });
+        items.collect::<Vec<_>>().join(LIST_SEPARATOR)
+    };
+    let aliases: Vec<String> = character.alias.iter().map(|alias| csv_list_item(alias, false)).collect();
+    vec![
+        character.name.clone(),
+        aliases.join(LIST_SEPARATOR),
+        class_cell(character.class),
+        character.element.map(|element| element.to_string()).unwrap_or_default(),
+        list(&character.advantages),
+        list(&character.disadvantages),
+    ]
+}
+
+/// Escapes a name for a CSV list cell: `\`, `;`, a leading `"`, whitespace at
+/// either end and, in matchup names, `:` are preceded by `\`, and an empty name
+/// is written as `""`.
+fn csv_list_item(name: &str, escape_colons: bool) -> String {
+    if name.is_empty() {
+        return "\"\"".to_string();
+    }
+    let last = name.chars().count() - 1;
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        let edge = i == 0 || i == last;
+        if c == '\\' || c == ';' || (escape_colons && c == ':') || (edge && c.is_whitespace()) || (i == 0 && c == '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_table(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut out = header.join(",");
+    out.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn markdown_table(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let escape = |cell: &str| cell.replace('|', "\\|").replace('\n', " ");
+    let mut out = format!("| {} |\n", header.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape(cell)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::edit::{EditError, Editor, MatchupList};
+use crate::error::{Error, Result};
+use crate::matchup::Matchup;
+use crate::store::read_source;
+
+/// Aliases and matchup entries of one character.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Changes {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alias: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advantages: Vec<Matchup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disadvantages: Vec<Matchup>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.alias.is_empty() && self.advantages.is_empty() && self.disadvantages.is_empty()
+    }
+
+    pub fn matchups(&self, list: MatchupList) -> &[Matchup] {
+        match list {
+            MatchupList::Advantages => &self.advantages,
+            MatchupList::Disadvantages => &self.disadvantages,
+        }
+    }
+
+    fn matchups_mut(&mut self, list: MatchupList) -> &mut Vec<Matchup> {
+        match list {
+            MatchupList::Advantages => &mut self.advantages,
+            MatchupList::Disadvantages => &mut self.disadvantages,
+        }
+    }
+}
+
+/// Changes to one character. Removals are applied before additions, so an
+/// entry can be both removed and added to change its weight.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverlayEntry {
+    /// Name or alias of the character.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Changes::is_empty")]
+    pub add: Changes,
+    #[serde(default, skip_serializing_if = "Changes::is_empty")]
+    pub remove: Changes,
+}
+
+/// Local changes applied on top of the character data at load time.
+///
+/// Stored as a JSON array of entries like
+/// `{"name": "Violet", "add": {"advantages": ["Mage"]}, "remove": {"alias": ["V"]}}`.
+/// Matchups to remove are matched by name as written in the data file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Overlay {
+    pub entries: Vec<OverlayEntry>,
+}
+
+/// What applying an [`Overlay`] did.
+#[derive(Debug, Clone, Default)]
+pub struct OverlayReport {
+    /// The changes that took effect, one entry per character, by canonical name.
+    pub applied: Vec<OverlayEntry>,
+    /// Changes that did not apply, e.g. removing an entry that is not listed.
+    pub skipped: Vec<EditError>,
+}
+
+impl OverlayReport {
+    /// The changes that took effect on the character named `name`.
+    pub fn get(&self, name: &str) -> Option<&OverlayEntry> {
+        self.applied.iter().find(|entry| entry.name == name)
+    }
+}
+
+impl Overlay {
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        let source = read_source(file_path.as_ref())?;
+        serde_json::from_str(&source).map_err(|err| Error::from(err).in_file(file_path))
+    }
+
+    /// Applies the overlay to raw characters, see [`Editor`].
+    pub fn apply(&self, editor: &mut Editor) -> OverlayReport {
+        let mut report = OverlayReport::default();
+        for entry in &self.entries {
+            let Some(name) = editor.get(&entry.name).map(|c| c.name.clone()) else {
+                report.skipped.push(EditError::UnknownCharacter(entry.name.clone()));
+                continue;
+            };
+            let index = match report.applied.iter().position(|applied| applied.name == name) {
+                Some(index) => index,
+                None => {
+                    report.applied.push(OverlayEntry { name: name.clone(), ..Default::default() });
+                    report.applied.len() - 1
+                }
+            };
+            let applied = &mut report.applied[index];
+            let skipped = &mut report.skipped;
+
+            for alias in &entry.remove.alias {
+                record(editor.remove_alias(&name, alias), alias, &mut applied.remove.alias, skipped);
+            }
+            for alias in &entry.add.alias {
+                record(editor.add_alias(&name, alias), alias, &mut applied.add.alias, skipped);
+            }
+            for list in [MatchupList::Advantages, MatchupList::Disadvantages] {
+                for matchup in entry.remove.matchups(list) {
+                    let result = editor.remove_matchup(&name, list, matchup.target.raw());
+                    record(result, matchup, applied.remove.matchups_mut(list), skipped);
+                }
+                for matchup in entry.add.matchups(list) {
+                    let result = editor.add_matchup(&name, list, matchup.clone());
+                    record(result, matchup, applied.add.matchups_mut(list), skipped);
+                }
+            }
+        }
+        report.applied.retain(|entry| !entry.add.is_empty() || !entry.remove.is_empty());
+        report
+    }
+}
+
+fn record<T: Clone>(
+    result: std::result::Result<(), EditError>,
+    value: &T,
+    applied: &mut Vec<T>,
+    skipped: &mut Vec<EditError>,
+) {
+    match result {
+        Ok(()) => applied.push(value.clone()),
+        Err(err) => skipped.push(err),
+    }
+}
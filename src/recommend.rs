@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::character::Character;
+use crate::db::CharacterDb;
+
+/// Above this many candidate teams the recommender switches from trying every
+/// combination to building the team greedily.
+const EXHAUSTIVE_LIMIT: u64 = 100_000;
+
+/// How one team member fares against the enemy lineup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TeamMember {
+    pub name: String,
+    /// Enemies this member counters, with the matchup score against each.
+    pub covers: Vec<(String, u32)>,
+    /// Enemies that counter this member, with the matchup score of each.
+    pub exposed_to: Vec<(String, u32)>,
+}
+
+impl TeamMember {
+    pub fn exposure(&self) -> u32 {
+        self.exposed_to.iter().fold(0, |sum, (_, score)| sum.saturating_add(*score))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Recommendation {
+    pub team: Vec<TeamMember>,
+    /// Sum over enemies of the best matchup score any team member has against them.
+    pub coverage: u32,
+    /// Sum of all enemy matchup scores against team members.
+    pub exposure: u32,
+    /// Enemies no team member counters.
+    pub uncovered: Vec<String>,
+}
+
+impl Recommendation {
+    /// Coverage minus exposure; higher is better.
+    pub fn score(&self) -> i64 {
+        i64::from(self.coverage) - i64::from(self.exposure)
+    }
+}
+
+/// Matchup scores between every candidate and every enemy.
+struct Scores<'db> {
+    candidates: Vec<&'db Character>,
+    enemies: Vec<&'db Character>,
+    /// `coverage[c][e]`: how well candidate `c` counters enemy `e`.
+    coverage: Vec<Vec<u32>>,
+    /// `exposure[c][e]`: how well enemy `e` counters candidate `c`.
+    exposure: Vec<Vec<u32>>,
+}
+
+impl<'db> Scores<'db> {
+    fn new(db: &'db CharacterDb, candidates: &[&'db Character], enemies: &[&'db Character]) -> Self {
+        let counter_scores = |character: &Character| -> HashMap<String, u32> {
+            db.find_counters(&character.name)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|counter| (counter.matchup.raw().to_string(), counter.score))
+                .collect()
+        };
+        let candidates: Vec<&Character> = candidates
+            .iter()
+            .copied()
+            .filter(|c| !enemies.iter().any(|e| e.name == c.name))
+            .collect();
+        let enemy_counters: Vec<HashMap<String, u32>> = enemies.iter().map(|e| counter_scores(e)).collect();
+        let coverage = candidates
+            .iter()
+            .map(|c| enemy_counters.iter().map(|counters| counters.get(&c.name).copied().unwrap_or(0)).collect())
+            .collect();
+        let exposure = candidates
+            .iter()
+            .map(|c| {
+                let counters = counter_scores(c);
+                enemies.iter().map(|e| counters.get(&e.name).copied().unwrap_or(0)).collect()
+            })
+            .collect();
+        Scores { candidates, enemies: enemies.to_vec(), coverage, exposure }
+    }
+
+    /// (coverage, exposure) of a team given as candidate indices.
+    fn evaluate(&self, team: &[usize]) -> (u32, u32) {
+        let coverage = (0..self.enemies.len())
+            .map(|e| team.iter().map(|&c| self.coverage[c][e]).max().unwrap_or(0))
+            .fold(0, u32::saturating_add);
+        let exposure = team
+            .iter()
+            .flat_map(|&c| self.exposure[c].iter().copied())
+            .fold(0, u32::saturating_add);
+        (coverage, exposure)
+    }
+
+    /// Orders teams: higher coverage minus exposure, then higher coverage.
+    fn better(&self, a: (u32, u32), b: (u32, u32)) -> bool {
+        let net = |(coverage, exposure): (u32, u32)| i64::from(coverage) - i64::from(exposure);
+        (net(a), a.0) > (net(b), b.0)
+    }
+
+    fn exhaustive(&self, size: usize) -> Vec<usize> {
+        let mut best: Option<(Vec<usize>, (u32, u32))> = None;
+        let mut team: Vec<usize> = (0..size).collect();
+        loop {
+            let score = self.evaluate(&team);
+            if best.as_ref().is_none_or(|(_, best_score)| self.better(score, *best_score)) {
+                best = Some((team.clone(), score));
+            }
+            // Advance to the next combination in lexicographic order
+            let n = self.candidates.len();
+            let Some(i) = (0..size).rev().find(|&i| team[i] < n - size + i) else {
+                break;
+            };
+            team[i] += 1;
+            for j in i + 1..size {
+                team[j] = team[j - 1] + 1;
+            }
+        }
+        best.map(|(team, _)| team).unwrap_or_default()
+    }
+
+    fn greedy(&self, size: usize) -> Vec<usize> {
+        let mut team: Vec<usize> = Vec::new();
+        while team.len() < size {
+            let next = (0..self.candidates.len())
+                .filter(|c| !team.contains(c))
+                .map(|c| {
+                    let mut extended = team.clone();
+                    extended.push(c);
+                    (c, self.evaluate(&extended))
+                })
+                .reduce(|best, next| if self.better(next.1, best.1) { next } else { best });
+            match next {
+                Some((c, _)) => team.push(c),
+                None => break,
+            }
+        }
+        team
+    }
+
+    fn recommendation(&self, team: &[usize]) -> Recommendation {
+        let named = |scores: &[u32]| -> Vec<(String, u32)> {
+            scores
+                .iter()
+                .zip(&self.enemies)
+                .filter(|(&score, _)| score > 0)
+                .map(|(&score, enemy)| (enemy.name.clone(), score))
+                .collect()
+        };
+        let (coverage, exposure) = self.evaluate(team);
+        let uncovered = (0..self.enemies.len())
+            .filter(|&e| team.iter().all(|&c| self.coverage[c][e] == 0))
+            .map(|e| self.enemies[e].name.clone())
+            .collect();
+        let team = team
+            .iter()
+            .map(|&c| TeamMember {
+                name: self.candidates[c].name.clone(),
+                covers: named(&self.coverage[c]),
+                exposed_to: named(&self.exposure[c]),
+            })
+            .collect();
+        Recommendation { team, coverage, exposure, uncovered }
+    }
+}
+
+impl CharacterDb {
+    /// Proposes the `size`-character team that best counters `enemies`.
+    ///
+    /// A team's coverage is, for each enemy, the best counter score any member
+    /// has against it (see [`CharacterDb::find_counters`]); its exposure is the
+    /// sum of the enemies' counter scores against each member. The team with
+    /// the highest coverage minus exposure wins. Enemies are never proposed.
+    pub fn recommend(&self, enemies: &[&Character], size: usize) -> Recommendation {
+        let candidates: Vec<&Character> = self.characters().iter().collect();
+        self.recommend_from(&candidates, enemies, size)
+    }
+
+    /// Like [`CharacterDb::recommend`], but only proposes characters from `candidates`.
+    pub fn recommend_from(&self, candidates: &[&Character], enemies: &[&Character], size: usize) -> Recommendation {
+        let scores = Scores::new(self, candidates, enemies);
+        let size = size.min(scores.candidates.len());
+        let team = if size == 0 {
+            Vec::new()
+        } else if combinations(scores.candidates.len() as u64, size as u64) <= EXHAUSTIVE_LIMIT {
+            scores.exhaustive(size)
+        } else {
+            scores.greedy(size)
+        };
+        scores.recommendation(&team)
+    }
+}
+
+fn combinations(n: u64, k: u64) -> u64 {
+    (0..k).fold(1u64, |acc, i| acc.saturating_mul(n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// X counters both enemies a little, Y and Z one each a lot, and W counters
+    /// E1 hardest but is countered by E2. E3 has no counters.
+    fn db() -> CharacterDb {
+        let character = |name: &str, advantages: serde_json::Value, disadvantages: serde_json::Value| {
+            json!({"name": name, "alias": [], "advantages": advantages, "disadvantages": disadvantages})
+        };
+        let characters = json!([
+            character("W", json!([{"name": "E1", "weight": 9}]), json!([{"name": "E2", "weight": 8}])),
+            character("X", json!([{"name": "E1", "weight": 3}, {"name": "E2", "weight": 3}]), json!([])),
+            character("Y", json!([{"name": "E1", "weight": 5}]), json!([])),
+            character("Z", json!([{"name": "E2", "weight": 5}]), json!([])),
+            character("E1", json!([]), json!([])),
+            character("E2", json!([]), json!([])),
+            character("E3", json!([]), json!([])),
+        ]);
+        CharacterDb::new(serde_json::from_value(characters).unwrap())
+    }
+
+    fn enemies<'db>(db: &'db CharacterDb, names: &[&str]) -> Vec<&'db Character> {
+        names.iter().map(|name| db.find(name).unwrap()).collect()
+    }
+
+    fn names(scores: &Scores, team: &[usize]) -> Vec<String> {
+        team.iter().map(|&c| scores.candidates[c].name.clone()).collect()
+    }
+
+    #[test]
+    fn exhaustive_search_finds_the_best_pair() {
+        let db = db();
+        let candidates: Vec<&Character> = db.characters().iter().collect();
+        let scores = Scores::new(&db, &candidates, &enemies(&db, &["E1", "E2"]));
+        assert_eq!(names(&scores, &[0, 1, 2, 3, 4]), ["W", "X", "Y", "Z", "E3"]);
+        let team = scores.exhaustive(2);
+        assert_eq!(names(&scores, &team), ["Y", "Z"]);
+        assert_eq!(scores.evaluate(&team), (10, 0));
+        // Ties keep the team found first
+        assert_eq!(names(&scores, &scores.exhaustive(3)), ["X", "Y", "Z"]);
+        assert_eq!(names(&scores, &scores.exhaustive(5)), ["W", "X", "Y", "Z", "E3"]);
+    }
+
+    #[test]
+    fn greedy_search_extends_the_best_single_pick() {
+        let db = db();
+        let candidates: Vec<&Character> = db.characters().iter().collect();
+        let scores = Scores::new(&db, &candidates, &enemies(&db, &["E1", "E2"]));
+        let team = scores.greedy(2);
+        assert_eq!(names(&scores, &team), ["X", "Y"]);
+        assert_eq!(scores.evaluate(&team), (8, 0));
+    }
+
+    #[test]
+    fn reports_coverage_exposure_and_uncovered_enemies() {
+        let db = db();
+        let enemies = enemies(&db, &["E1", "E2", "E3"]);
+        let recommendation = db.recommend(&enemies, 2);
+        let team: Vec<&str> = recommendation.team.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(team, ["Y", "Z"]);
+        assert_eq!((recommendation.coverage, recommendation.exposure, recommendation.score()), (10, 0, 10));
+        assert_eq!(recommendation.uncovered, ["E3"]);
+
+        let w = db.find("W").unwrap();
+        let recommendation = db.recommend_from(&[w], &enemies, 4);
+        let member = &recommendation.team[0];
+        assert_eq!(member.covers, [("E1".to_string(), 9)]);
+        assert_eq!(member.exposed_to, [("E2".to_string(), 8)]);
+        assert_eq!((recommendation.coverage, recommendation.exposure, recommendation.score()), (9, 8, 1));
+        assert_eq!(recommendation.uncovered, ["E2", "E3"]);
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::character::Character;
+use crate::db::{CharacterDb, Counter, Threat};
+use crate::error::{Error, Result};
+use crate::matchup::MatchupRef;
+use crate::store::read_source;
+
+/// An owned character, optionally with how much has been invested in it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RawRosterEntry")]
+pub struct RosterEntry {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub investment: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRosterEntry {
+    Plain(String),
+    Detailed { name: String, investment: Option<u32> },
+}
+
+impl From<RawRosterEntry> for RosterEntry {
+    fn from(raw: RawRosterEntry) -> Self {
+        match raw {
+            RawRosterEntry::Plain(name) => RosterEntry { name, investment: None },
+            RawRosterEntry::Detailed { name, investment } => RosterEntry { name, investment },
+        }
+    }
+}
+
+/// The characters a player owns.
+///
+/// Stored as a JSON array of names or aliases, each optionally written as
+/// `{"name": "...", "investment": 3}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Roster {
+    pub entries: Vec<RosterEntry>,
+}
+
+impl Roster {
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        let source = read_source(file_path.as_ref())?;
+        serde_json::from_str(&source).map_err(|err| Error::from(err).in_file(file_path))
+    }
+
+    /// Rewrites every entry to the canonical name of the character it refers
+    /// to. Returns the entries that match no character; those are kept as is.
+    pub fn resolve(&mut self, db: &CharacterDb) -> Vec<String> {
+        let mut unknown = Vec::new();
+        for entry in &mut self.entries {
+            match db.find(&entry.name) {
+                Some(character) => entry.name = character.name.clone(),
+                None => unknown.push(entry.name.clone()),
+            }
+        }
+        unknown
+    }
+
+    pub fn entry(&self, character: &Character) -> Option<&RosterEntry> {
+        self.entries.iter().find(|entry| entry.name == character.name)
+    }
+
+    pub fn owns(&self, character: &Character) -> bool {
+        self.entry(character).is_some()
+    }
+
+    /// Ownership of a matchup reference; `None` for classes and unknown names.
+    pub fn owns_ref(&self, matchup: &MatchupRef) -> Option<bool> {
+        match matchup {
+            MatchupRef::Character(name) => Some(self.entries.iter().any(|entry| entry.name == *name)),
+            _ => None,
+        }
+    }
+
+    /// Fills in `owned` on each threat.
+    pub fn mark_threats(&self, threats: &mut [Threat]) {
+        for threat in threats {
+            threat.owned = self.owns_ref(&threat.matchup);
+        }
+    }
+
+    /// Marks threats and drops those that are not owned characters.
+    pub fn retain_owned_threats(&self, threats: &mut Vec<Threat>) {
+        self.mark_threats(threats);
+        threats.retain(|threat| threat.owned == Some(true));
+    }
+
+    /// Marks counters and drops those that are not owned characters.
+    pub fn retain_owned_counters(&self, counters: &mut Vec<Counter>) {
+        self.mark_counters(counters);
+        counters.retain(|counter| counter.owned == Some(true));
+    }
+
+    /// Fills in `owned` on each counter.
+    pub fn mark_counters(&self, counters: &mut [Counter]) {
+        for counter in counters {
+            counter.owned = self.owns_ref(&counter.matchup);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Versions of the data file layout.
+//!
+//! 1. A bare list of characters (or a single character, or a TOML
+//!    `[[characters]]` array of tables without a version).
+//! 2. `{"version": 2, "characters": [...]}`.
+//!
+//! Older layouts are upgraded in memory when read, one version at a time;
+//! [`migrate_file`] rewrites a file in the latest layout.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::character::Character;
+use crate::error::{Error, Result};
+use crate::format::DataFormat;
+use crate::store::{read_source, save_characters_as, write_atomic};
+
+/// The version of the layout data files are written in.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades data from the version after its index to the next one.
+const MIGRATIONS: [fn(Value) -> Value; SCHEMA_VERSION as usize - 1] = [wrap_characters];
+
+/// A data file in the latest layout.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Document<'a> {
+    pub version: u32,
+    pub characters: Cow<'a, [Character]>,
+}
+
+impl<'a> Document<'a> {
+    pub fn new(characters: &'a [Character]) -> Self {
+        Document { version: SCHEMA_VERSION, characters: Cow::Borrowed(characters) }
+    }
+}
+
+/// The layout version of parsed data; data without a `version` is version 1.
+pub(crate) fn schema_version(value: &Value) -> Result<u32> {
+    let Some(version) = value.as_object().and_then(|root| root.get("version")) else {
+        return Ok(1);
+    };
+    match version.as_u64() {
+        Some(version) if (1..=SCHEMA_VERSION as u64).contains(&version) => Ok(version as u32),
+        Some(version) if version > SCHEMA_VERSION as u64 => Err(error(format!(
+            "schema version {} is newer than this program supports (up to {})",
+            version, SCHEMA_VERSION
+        ))),
+        _ => Err(error(format!("invalid schema version: {}", version))),
+    }
+}
+
+/// Upgrades parsed data of any supported version to the latest layout.
+pub(crate) fn migrate(value: Value) -> Result<Value> {
+    let version = schema_version(&value)?;
+    Ok(MIGRATIONS[version as usize - 1..].iter().fold(value, |value, migration| migration(value)))
+}
+
+/// 1 to 2: puts the characters under `characters` next to the version.
+fn wrap_characters(value: Value) -> Value {
+    let characters = match value {
+        Value::Object(ref root) if root.contains_key("name") => Value::Array(vec![value]),
+        Value::Object(mut root) => root.remove("characters").unwrap_or_else(|| Value::Array(Vec::new())),
+        value => value,
+    };
+    json!({ "version": 2, "characters": characters })
+}
+
+fn error(message: String) -> Error {
+    Error::Parse { file: None, line: 0, column: 0, message }
+}
+
+/// What [`migrate_file`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// The version the file was in.
+    pub from: u32,
+    /// Where the original file was copied to, unless it already was in the
+    /// latest layout and was left alone.
+    pub backup: Option<PathBuf>,
+}
+
+/// Rewrites a data file in the latest layout, after copying the original to
+/// `<file>.bak`. The format follows the extension, see
+/// [`DataFormat::from_path`].
+pub fn migrate_file<P: AsRef<Path>>(file_path: P) -> Result<Migration> {
+    let path = file_path.as_ref();
+    let format = DataFormat::from_path(path);
+    let source = read_source(path)?;
+    let value = format.parse(&source).map_err(|err| err.in_file(path))?;
+    let from = schema_version(&value).map_err(|err| err.in_file(path))?;
+    if from == SCHEMA_VERSION {
+        return Ok(Migration { from, backup: None });
+    }
+    let document: Document = serde_json::from_value(migrate(value)?).map_err(|err| Error::from(err).in_file(path))?;
+
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    write_atomic(&backup, source.as_bytes())?;
+    save_characters_as(path, &document.characters, format)?;
+    Ok(Migration { from, backup: Some(backup) })
+}
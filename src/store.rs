@@ -0,0 +1,204 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::ser::Formatter;
+use serde_json::{Map, Value};
+
+use crate::character::Character;
+use crate::error::{Error, Result};
+use crate::format::DataFormat;
+use crate::schema::{Document, SCHEMA_VERSION};
+use crate::{toml, yaml};
+
+/// Serializes characters in the layout `characters.json` is written in: a
+/// [`SCHEMA_VERSION`](crate::SCHEMA_VERSION) document with one object per
+/// character with one key per line, and lists kept on one line.
+pub fn to_json_string(characters: &[Character]) -> String {
+    to_data_string(characters, DataFormat::Json)
+}
+
+/// Serializes characters in `format` in the latest schema version; JSON uses
+/// the [`to_json_string`] layout.
+pub fn to_data_string(characters: &[Character], format: DataFormat) -> String {
+    to_data_string_in(characters, format, SCHEMA_VERSION)
+}
+
+/// Serializes characters in `format` in the layout of schema `version`, e.g.
+/// to write a file back in the version it was read in.
+pub(crate) fn to_data_string_in(characters: &[Character], format: DataFormat, version: u32) -> String {
+    // Character objects are the deepest block level in every layout
+    let bare = version < 2;
+    let block_depth = if bare { 2 } else { 3 };
+    let value = || {
+        let value = if bare { serde_json::to_value(characters) } else { serde_json::to_value(Document::new(characters)) };
+        value.expect("characters always serialize")
+    };
+    match format {
+        DataFormat::Json if bare => json_string(&characters, block_depth),
+        DataFormat::Json => json_string(&Document::new(characters), block_depth),
+        DataFormat::Yaml => yaml::to_string(&value(), block_depth),
+        DataFormat::Toml => match value() {
+            Value::Object(root) => toml::to_string(&root),
+            characters => {
+                let mut root = Map::new();
+                root.insert("characters".to_string(), characters);
+                toml::to_string(&root)
+            }
+        },
+    }
+}
+
+fn json_string<T: Serialize>(value: &T, block_depth: usize) -> String {
+    let mut out = Vec::new();
+    let formatter = DataFormatter { open: Vec::new(), block_depth };
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer).expect("characters always serialize");
+    out.push(b'\n');
+    String::from_utf8(out).expect("serde_json writes UTF-8")
+}
+
+/// Writes characters to `file_path` atomically: the data goes to a temporary
+/// file next to it first, which then replaces the original. The format
+/// follows the extension, see [`DataFormat::from_path`].
+pub fn save_characters<P: AsRef<Path>>(file_path: P, characters: &[Character]) -> Result<()> {
+    let format = DataFormat::from_path(&file_path);
+    save_characters_as(file_path, characters, format)
+}
+
+pub fn save_characters_as<P: AsRef<Path>>(file_path: P, characters: &[Character], format: DataFormat) -> Result<()> {
+    write_atomic(file_path.as_ref(), to_data_string(characters, format).as_bytes())
+}
+
+/// Like [`save_characters`], but in the layout of schema `version`.
+pub(crate) fn save_characters_in(file_path: &Path, characters: &[Character], version: u32) -> Result<()> {
+    let format = DataFormat::from_path(file_path);
+    write_atomic(file_path, to_data_string_in(characters, format, version).as_bytes())
+}
+
+/// The file name that stands for standard input.
+pub const STDIN: &str = "-";
+
+pub(crate) fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN
+}
+
+/// The format of `source`, read from `path`: by extension, or guessed from the
+/// contents for standard input.
+pub(crate) fn format_of(path: &Path, source: &str) -> DataFormat {
+    if is_stdin(path) {
+        DataFormat::detect(source)
+    } else {
+        DataFormat::from_path(path)
+    }
+}
+
+/// Reads a whole file, or standard input for [`STDIN`], reporting a missing
+/// file as [`Error::FileNotFound`].
+pub(crate) fn read_source(path: &Path) -> Result<String> {
+    if is_stdin(path) {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        return Ok(source);
+    }
+    fs::read_to_string(path).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => Error::FileNotFound(path.to_path_buf()),
+        _ => Error::Io(err),
+    })
+}
+
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let file_name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    let result = fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(err) = result.and_then(|_| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// Pretty-printer that indents the document, its characters and each character object
+/// but keeps their lists (and weighted entries) inline.
+struct DataFormatter {
+    /// For each open container, whether it has had any elements yet.
+    open: Vec<bool>,
+    /// Containers nested deeper than this are written on a single line.
+    block_depth: usize,
+}
+
+impl DataFormatter {
+    fn is_block(&self) -> bool {
+        self.open.len() <= self.block_depth
+    }
+
+    fn begin<W: ?Sized + Write>(&mut self, writer: &mut W, bracket: &[u8]) -> io::Result<()> {
+        self.open.push(false);
+        writer.write_all(bracket)
+    }
+
+    fn end<W: ?Sized + Write>(&mut self, writer: &mut W, bracket: &[u8]) -> io::Result<()> {
+        let block = self.is_block();
+        let had_elements = self.open.pop().unwrap_or(false);
+        if block && had_elements {
+            writer.write_all(b"\n")?;
+            self.indent(writer, self.open.len())?;
+        }
+        writer.write_all(bracket)
+    }
+
+    fn element<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        if let Some(had_elements) = self.open.last_mut() {
+            *had_elements = true;
+        }
+        if self.is_block() {
+            writer.write_all(if first { b"\n" } else { b",\n" })?;
+            self.indent(writer, self.open.len())
+        } else if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn indent<W: ?Sized + Write>(&self, writer: &mut W, depth: usize) -> io::Result<()> {
+        for _ in 0..depth {
+            writer.write_all(b"  ")?;
+        }
+        Ok(())
+    }
+}
+
+impl Formatter for DataFormatter {
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.begin(writer, b"[")
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.end(writer, b"]")
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.element(writer, first)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.begin(writer, b"{")
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.end(writer, b"}")
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.element(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
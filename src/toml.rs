@@ -0,0 +1,462 @@
+//! The subset of TOML used for character data: `[table]` and `[[array]]`
+//! headers with simple keys, and key/value pairs holding strings, integers,
+//! floats, booleans, arrays and inline tables. Dotted keys, multi-line strings
+//! and dates are not supported.
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::{Error, Result};
+use crate::format::ordered;
+
+/// The table that key/value pairs currently go into.
+enum Header {
+    Root,
+    Table(String),
+    /// The last element of an array of tables.
+    ArrayTable(String),
+}
+
+pub(crate) fn parse(source: &str) -> Result<Value> {
+    let mut parser = Parser { chars: source.chars().collect(), pos: 0, line: 1, column: 1 };
+    let mut root = Map::new();
+    let mut header = Header::Root;
+
+    loop {
+        parser.skip_trivia();
+        let (line, column) = (parser.line, parser.column);
+        match parser.peek() {
+            None => break,
+            Some('[') => {
+                parser.bump();
+                let array = parser.peek() == Some('[');
+                if array {
+                    parser.bump();
+                }
+                parser.skip_spaces();
+                let key = parser.key()?;
+                parser.skip_spaces();
+                parser.expect(']')?;
+                if array {
+                    parser.expect(']')?;
+                }
+                header = if array {
+                    let tables = root.entry(key.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                    let Value::Array(tables) = tables else {
+                        return Err(error(line, column, format!("{} is not an array of tables", key)));
+                    };
+                    tables.push(Value::Object(Map::new()));
+                    Header::ArrayTable(key)
+                } else {
+                    if root.contains_key(&key) {
+                        return Err(error(line, column, format!("table {} is defined twice", key)));
+                    }
+                    root.insert(key.clone(), Value::Object(Map::new()));
+                    Header::Table(key)
+                };
+            }
+            Some(_) => {
+                let key = parser.key()?;
+                parser.skip_spaces();
+                parser.expect('=')?;
+                parser.skip_spaces();
+                let value = parser.value()?;
+                if current(&mut root, &header).insert(key.clone(), value).is_some() {
+                    return Err(error(line, column, format!("duplicate key: {}", key)));
+                }
+            }
+        }
+        parser.end_of_line()?;
+    }
+    Ok(Value::Object(root))
+}
+
+fn current<'m>(root: &'m mut Map<String, Value>, header: &Header) -> &'m mut Map<String, Value> {
+    let table = match header {
+        Header::Root => return root,
+        Header::Table(key) => root.get_mut(key),
+        Header::ArrayTable(key) => root.get_mut(key).and_then(Value::as_array_mut).and_then(|tables| tables.last_mut()),
+    };
+    table.and_then(Value::as_object_mut).expect("headers create their table")
+}
+
+fn error(line: usize, column: usize, message: impl Into<String>) -> Error {
+    Error::Parse { file: None, line, column, message: message.into() }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Parser {
+    fn error(&self, message: impl Into<String>) -> Error {
+        error(self.line, self.column, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            _ => Err(self.error(format!("expected `{}`", expected))),
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') | Some('\r') => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_spaces();
+        self.skip_comment();
+        if self.peek() == Some('\r') {
+            self.bump();
+        }
+        match self.peek() {
+            None | Some('\n') => Ok(()),
+            Some(_) => Err(self.error("expected the end of the line")),
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        let key = match self.peek() {
+            Some('"') => self.basic_string()?,
+            Some('\'') => self.literal_string()?,
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    self.bump();
+                }
+                if self.pos == start {
+                    return Err(self.error("expected a key"));
+                }
+                self.chars[start..self.pos].iter().collect()
+            }
+        };
+        self.skip_spaces();
+        if self.peek() == Some('.') {
+            return Err(self.error("dotted keys are not supported"));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('\'') => Ok(Value::String(self.literal_string()?)),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => self.bare_value(),
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_trivia();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => {
+                    self.bump();
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        let mut map = Map::new();
+        self.skip_spaces();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Value::Object(map));
+        }
+        loop {
+            self.skip_spaces();
+            let (line, column) = (self.line, self.column);
+            let key = self.key()?;
+            self.expect('=')?;
+            self.skip_spaces();
+            let value = self.value()?;
+            if map.insert(key.clone(), value).is_some() {
+                return Err(error(line, column, format!("duplicate key: {}", key)));
+            }
+            self.skip_spaces();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some('}') => {
+                    self.bump();
+                    return Ok(Value::Object(map));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        if self.peek() == Some('"') && self.chars.get(self.pos + 1) == Some(&'"') {
+            return Err(self.error("multi-line strings are not supported"));
+        }
+        let mut value = String::new();
+        loop {
+            // Checked before consuming, so the error points at the string's line
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) => {
+                    self.bump();
+                    match c {
+                        '"' => return Ok(value),
+                        '\\' => value.push(self.escape()?),
+                        c => value.push(c),
+                    }
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char> {
+        let c = match self.bump() {
+            Some('b') => '\u{8}',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('f') => '\u{c}',
+            Some('r') => '\r',
+            Some(c @ ('"' | '\\')) => c,
+            Some(u @ ('u' | 'U')) => {
+                let len = if u == 'u' { 4 } else { 8 };
+                let hex: String = self.chars.iter().skip(self.pos).take(len).collect();
+                let code = u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == len);
+                let c = code.and_then(char::from_u32).ok_or_else(|| self.error("invalid unicode escape"))?;
+                for _ in 0..len {
+                    self.bump();
+                }
+                c
+            }
+            _ => return Err(self.error("unknown escape sequence")),
+        };
+        Ok(c)
+    }
+
+    fn literal_string(&mut self) -> Result<String> {
+        self.expect('\'')?;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) => {
+                    self.bump();
+                    if c == '\'' {
+                        return Ok(value);
+                    }
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    /// Booleans and numbers.
+    fn bare_value(&mut self) -> Result<Value> {
+        let (line, column) = (self.line, self.column);
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+            self.bump();
+        }
+        let text: String = self.chars[start..self.pos].iter().filter(|&&c| c != '_').collect();
+        let value = match text.as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => text
+                .parse::<i64>()
+                .ok()
+                .map(Value::from)
+                .or_else(|| text.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number)),
+        };
+        value.ok_or_else(|| error(line, column, "unsupported value"))
+    }
+}
+
+/// Writes a table as TOML: plain values first, then nested tables, then each
+/// array of tables as `[[key]]` sections. Null values are left out, as TOML
+/// has no null.
+pub(crate) fn to_string(root: &Map<String, Value>) -> String {
+    let is_table_array =
+        |value: &Value| matches!(value, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object));
+    let mut out = String::new();
+    let entries = ordered(root);
+    write_pairs(&mut out, entries.iter().filter(|(_, value)| !value.is_object() && !is_table_array(value)));
+    for (key, value) in &entries {
+        if let Value::Object(table) = value {
+            out.push_str(&format!("\n[{}]\n", toml_key(key)));
+            write_pairs(&mut out, &ordered(table));
+        }
+    }
+    for (key, value) in entries.iter().filter(|(_, value)| is_table_array(value)) {
+        for table in value.as_array().into_iter().flatten().filter_map(Value::as_object) {
+            out.push_str(&format!("\n[[{}]]\n", toml_key(key)));
+            write_pairs(&mut out, &ordered(table));
+        }
+    }
+    out.trim_start_matches('\n').to_string()
+}
+
+fn write_pairs<'a>(out: &mut String, pairs: impl IntoIterator<Item = &'a (&'a String, &'a Value)>) {
+    for (key, value) in pairs.into_iter().filter(|(_, value)| !value.is_null()) {
+        out.push_str(&format!("{} = {}\n", toml_key(key), inline(value)));
+    }
+}
+
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        // JSON string escapes are all valid in TOML basic strings
+        Value::String(s) => serde_json::to_string(s).expect("strings always serialize"),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter(|item| !item.is_null()).map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => {
+            let entries: Vec<String> = ordered(map)
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| format!("{} = {}", toml_key(key), inline(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+    }
+}
+
+fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).expect("strings always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip(value: &Value) -> Value {
+        parse(&to_string(value.as_object().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn round_trips_character_data() {
+        let value = json!({
+            "version": 2,
+            "characters": [
+                {
+                    "name": "深淵のユピネ",
+                    "alias": ["Abyssal Yufine", "say \"hi\"", "tab\there", "back\\slash", "0x1F", "true"],
+                    "advantages": ["Mage", {"name": "ヴィオレタ", "weight": 2}],
+                    "disadvantages": [],
+                    "element": "fire",
+                },
+                {"name": "B", "alias": [], "advantages": [], "disadvantages": []},
+            ],
+        });
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn writes_plain_values_before_tables() {
+        let value = json!({"characters": [{"name": "A", "alias": []}], "version": 2});
+        assert_eq!(to_string(value.as_object().unwrap()), "version = 2\n\n[[characters]]\nname = \"A\"\nalias = []\n");
+    }
+
+    #[test]
+    fn reads_strings_numbers_and_tables() {
+        let source = "# data\nversion = 2\n\n[meta]\n'literal key' = 'C:\\path'\nsize = 1_000\nratio = 0.5\non = true\n\n[[characters]]\nname = \"\\u30e6\" # trailing\nalias = [\n  \"a\",\n  \"b\",\n]\n";
+        let expected = json!({
+            "version": 2,
+            "meta": {"literal key": "C:\\path", "size": 1000, "ratio": 0.5, "on": true},
+            "characters": [{"name": "ユ", "alias": ["a", "b"]}],
+        });
+        assert_eq!(parse(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_unsupported_or_invalid_input_with_a_position() {
+        let cases = [
+            ("a.b = 1\n", 1),
+            ("a = \"\"\"x\"\"\"\n", 1),
+            ("a = 1979-05-27\n", 1),
+            ("a = 1\na = 2\n", 2),
+            ("[t]\n[t]\n", 2),
+            ("a = \"open\n", 1),
+            ("a = 1 b = 2\n", 1),
+            ("a = \"\\q\"\n", 1),
+            ("a = [1, 2\n", 2),
+        ];
+        for (source, line) in cases {
+            match parse(source) {
+                Err(Error::Parse { line: found, .. }) => assert_eq!(found, line, "{:?}", source),
+                other => panic!("{:?} should not parse: {:?}", source, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::character::Character;
+use crate::db::{parse_characters_as, CharacterDb};
+use crate::error::Result;
+use crate::format::DataFormat;
+use crate::locate::{json_positions, Position};
+use crate::matchup::MatchupRef;
+use crate::store::{format_of, read_source};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// A matchup entry that names neither a known character nor a class.
+    DanglingReference { value: String },
+    /// An empty `""` string in a name, alias or matchup list.
+    EmptyString,
+    /// The same `name` is defined by more than one entry.
+    DuplicateName,
+    /// An alias that is also the name or alias of another character.
+    AliasCollision { alias: String, other: String },
+    /// A character that lists itself as an advantage or disadvantage.
+    SelfReference,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Name of the character the issue was found in.
+    pub character: String,
+    /// JSON pointer to the offending value, e.g. `/3/advantages/1`.
+    pub pointer: String,
+    pub position: Option<Position>,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(position) = self.position {
+            write!(f, "{}: ", position)?;
+        }
+        write!(f, "{} ({}): {}", self.character, self.pointer, self.kind)
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::DanglingReference { value } => write!(f, "reference to unknown character \"{}\"", value),
+            IssueKind::EmptyString => write!(f, "empty string"),
+            IssueKind::DuplicateName => write!(f, "duplicate character name"),
+            IssueKind::AliasCollision { alias, other } => {
+                write!(f, "alias \"{}\" is also used by {}", alias, other)
+            }
+            IssueKind::SelfReference => write!(f, "character references itself"),
+        }
+    }
+}
+
+/// Loads a data file and checks it for referential integrity problems.
+pub fn validate_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<Issue>> {
+    let source = read_source(file_path.as_ref())?;
+    let format = format_of(file_path.as_ref(), &source);
+    let characters = parse_characters_as(&source, format).map_err(|err| err.in_file(file_path))?;
+    // Positions are only located in JSON sources
+    Ok(validate(&characters, Some(source.as_str()).filter(|_| format == DataFormat::Json)))
+}
+
+/// Checks `characters` for referential integrity problems.
+///
+/// When the JSON `source` the characters were parsed from is given, each issue
+/// carries the line/column of the offending value.
+pub fn validate(characters: &[Character], source: Option<&str>) -> Vec<Issue> {
+    let positions = source.map(json_positions).unwrap_or_default();
+    let db = CharacterDb::new(characters.to_vec());
+    let mut issues = Vec::new();
+    let mut push = |character: &Character, pointer: String, kind: IssueKind| {
+        issues.push(Issue {
+            character: character.name.clone(),
+            // Versioned documents keep the characters under `/characters`
+            position: positions.get(&pointer).or_else(|| positions.get(&format!("/characters{}", pointer))).copied(),
+            pointer,
+            kind,
+        });
+    };
+
+    let mut first_index: HashMap<&str, usize> = HashMap::new();
+    for (index, character) in characters.iter().enumerate() {
+        if character.name.trim().is_empty() {
+            push(character, format!("/{}/name", index), IssueKind::EmptyString);
+        } else if first_index.contains_key(character.name.as_str()) {
+            push(character, format!("/{}/name", index), IssueKind::DuplicateName);
+        } else {
+            first_index.insert(&character.name, index);
+        }
+    }
+
+    for (index, character) in characters.iter().enumerate() {
+        for (alias_index, alias) in character.alias.iter().enumerate() {
+            let pointer = format!("/{}/alias/{}", index, alias_index);
+            if alias.trim().is_empty() {
+                push(character, pointer, IssueKind::EmptyString);
+                continue;
+            }
+            let collisions = characters
+                .iter()
+                .enumerate()
+                .filter(|(other_index, other)| *other_index != index && other.matches(alias));
+            for (_, other) in collisions {
+                push(
+                    character,
+                    pointer.clone(),
+                    IssueKind::AliasCollision { alias: alias.clone(), other: other.name.clone() },
+                );
+            }
+        }
+
+        let resolved = &db.characters()[index];
+        let lists = [("advantages", &resolved.advantages), ("disadvantages", &resolved.disadvantages)];
+        for (field, matchups) in lists {
+            for (matchup_index, matchup) in matchups.iter().enumerate() {
+                let pointer = format!("/{}/{}/{}", index, field, matchup_index);
+                match &matchup.target {
+                    m if m.is_empty() => push(character, pointer, IssueKind::EmptyString),
+                    MatchupRef::Unresolved(value) => {
+                        push(character, pointer, IssueKind::DanglingReference { value: value.clone() })
+                    }
+                    MatchupRef::Character(name) if *name == character.name => {
+                        push(character, pointer, IssueKind::SelfReference)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Issues in `after` that `before` did not have, e.g. to reject an edit that
+/// introduces a dangling reference. Issues are compared by character and kind,
+/// since positions shift when entries are added or removed, and each old issue
+/// accounts for one new one, so a second identical issue counts. A character
+/// renamed in place (same index, old name gone, new name not used before)
+/// keeps its issues, as do the collisions other characters have with it.
+pub fn new_issues(before: &[Character], after: &[Character]) -> Vec<Issue> {
+    let renamed: HashMap<&str, &str> = before
+        .iter()
+        .zip(after)
+        .filter(|(old, new)| {
+            old.name != new.name
+                && !after.iter().any(|c| c.name == old.name)
+                && !before.iter().any(|c| c.name == new.name)
+        })
+        .map(|(old, new)| (old.name.as_str(), new.name.as_str()))
+        .collect();
+    let rename = |name: &str| renamed.get(name).copied().unwrap_or(name).to_string();
+    let mut existing: Vec<(String, IssueKind)> = validate(before, None)
+        .into_iter()
+        .map(|issue| {
+            let kind = match issue.kind {
+                IssueKind::AliasCollision { alias, other } => IssueKind::AliasCollision { alias, other: rename(&other) },
+                kind => kind,
+            };
+            (rename(&issue.character), kind)
+        })
+        .collect();
+    validate(after, None)
+        .into_iter()
+        .filter(|issue| {
+            match existing.iter().position(|(character, kind)| *character == issue.character && *kind == issue.kind) {
+                Some(index) => {
+                    existing.swap_remove(index);
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn characters(value: serde_json::Value) -> Vec<Character> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn kinds(issues: &[Issue]) -> Vec<(&str, &IssueKind)> {
+        issues.iter().map(|issue| (issue.character.as_str(), &issue.kind)).collect()
+    }
+
+    #[test]
+    fn keeps_existing_issues_over_a_rename() {
+        let before = characters(json!([
+            {"name": "A", "alias": ["X"], "advantages": ["Nobody"], "disadvantages": []},
+            {"name": "B", "alias": ["X"], "advantages": [], "disadvantages": []},
+        ]));
+        let after = characters(json!([
+            {"name": "C", "alias": ["X"], "advantages": ["Nobody"], "disadvantages": []},
+            {"name": "B", "alias": ["X"], "advantages": [], "disadvantages": []},
+        ]));
+        assert_eq!(validate(&after, None).len(), 3);
+        assert!(new_issues(&before, &after).is_empty());
+    }
+
+    #[test]
+    fn does_not_carry_issues_onto_a_name_used_before() {
+        // Removing B and renaming A to B is not a rename of A, so its issue is new for B
+        let before = characters(json!([
+            {"name": "A", "alias": [], "advantages": ["Nobody"], "disadvantages": []},
+            {"name": "B", "alias": [], "advantages": [], "disadvantages": []},
+        ]));
+        let after = characters(json!([
+            {"name": "B", "alias": [], "advantages": ["Nobody"], "disadvantages": []},
+        ]));
+        let dangling = IssueKind::DanglingReference { value: "Nobody".to_string() };
+        assert_eq!(kinds(&new_issues(&before, &after)), [("B", &dangling)]);
+    }
+
+    #[test]
+    fn counts_a_second_identical_issue() {
+        let before = characters(json!([
+            {"name": "A", "alias": [], "advantages": ["Nobody"], "disadvantages": []},
+        ]));
+        let after = characters(json!([
+            {"name": "A", "alias": [], "advantages": ["Nobody"], "disadvantages": ["Nobody"]},
+        ]));
+        let dangling = IssueKind::DanglingReference { value: "Nobody".to_string() };
+        assert_eq!(kinds(&new_issues(&before, &after)), [("A", &dangling)]);
+        assert!(new_issues(&after, &after).is_empty());
+    }
+}
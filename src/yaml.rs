@@ -0,0 +1,565 @@
+//! The subset of YAML used for character data: block mappings and sequences,
+//! single-line flow collections (`[a, b]`, `{name: x, weight: 2}`), plain and
+//! quoted scalars, and comments. Anchors, tags, block scalars (`|`, `>`) and
+//! multiple documents are not supported.
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::{Error, Result};
+use crate::format::ordered;
+
+fn error(line: usize, column: usize, message: impl Into<String>) -> Error {
+    Error::Parse { file: None, line, column, message: message.into() }
+}
+
+/// A non-blank line with its comment and indentation stripped.
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+pub(crate) fn parse(source: &str) -> Result<Value> {
+    let mut lines = Vec::new();
+    for (index, raw) in source.lines().enumerate() {
+        let text = strip_comment(raw).trim_end();
+        let content = text.trim_start_matches(' ');
+        let indent = text.len() - content.len();
+        if content.is_empty() || (lines.is_empty() && content == "---") {
+            continue;
+        }
+        if content.starts_with('\t') {
+            return Err(error(index + 1, indent + 1, "tabs are not allowed in indentation"));
+        }
+        lines.push(Line { number: index + 1, indent, text: content });
+    }
+    let Some(first) = lines.first().copied() else {
+        return Ok(Value::Null);
+    };
+
+    let mut parser = Parser { lines, pos: 0 };
+    let value = parser.block(first.indent)?;
+    match parser.lines.get(parser.pos) {
+        Some(line) => Err(error(line.number, line.indent + 1, "unexpected indentation")),
+        None => Ok(value),
+    }
+}
+
+/// Cuts a `#` comment that starts a line or follows whitespace, outside quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    let mut escaped = false;
+    let mut closed_single = false;
+    for (i, c) in line.char_indices() {
+        let just_closed = std::mem::take(&mut closed_single);
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => {
+                quote = None;
+                closed_single = q == '\'';
+            }
+            Some(_) => {}
+            // `''` inside single quotes stands for the quote itself
+            None if c == '\'' && just_closed => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return &line[..i],
+            None if (c == '"' || c == '\'') && (previous.is_whitespace() || "[{,:-".contains(previous)) => {
+                quote = Some(c)
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses the node starting at the current line, which is indented by `indent`.
+    fn block(&mut self, indent: usize) -> Result<Value> {
+        let line = self.lines[self.pos];
+        if is_item(line.text) {
+            self.sequence(indent)
+        } else if split_key(line)?.is_some() {
+            self.mapping(indent)
+        } else {
+            self.pos += 1;
+            Inline::new(line, line.text).value_to_end()
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Value> {
+        let mut items = Vec::new();
+        while let Some(&line) = self.lines.get(self.pos) {
+            if line.indent > indent {
+                return Err(error(line.number, line.indent + 1, "unexpected indentation"));
+            }
+            if line.indent < indent || !is_item(line.text) {
+                break;
+            }
+            let rest = line.text[1..].trim_start_matches(' ');
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.nested(indent)?);
+            } else {
+                // Parse `- key: value` as if the text after the dash started its own line
+                let offset = line.text.len() - rest.len();
+                self.lines[self.pos] = Line { indent: indent + offset, text: rest, ..line };
+                items.push(self.block(indent + offset)?);
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Value> {
+        let mut map = Map::new();
+        while let Some(&line) = self.lines.get(self.pos) {
+            if line.indent > indent {
+                return Err(error(line.number, line.indent + 1, "unexpected indentation"));
+            }
+            if line.indent < indent {
+                break;
+            }
+            let Some((key, rest)) = split_key(line)? else {
+                return Err(error(line.number, line.indent + 1, "expected `key: value`"));
+            };
+            self.pos += 1;
+            let value = if rest.is_empty() {
+                match self.lines.get(self.pos) {
+                    Some(next) if next.indent == indent && is_item(next.text) => self.sequence(indent)?,
+                    _ => self.nested(indent)?,
+                }
+            } else {
+                Inline::new(line, rest).value_to_end()?
+            };
+            if map.insert(key.clone(), value).is_some() {
+                return Err(error(line.number, line.indent + 1, format!("duplicate key: {}", key)));
+            }
+        }
+        Ok(Value::Object(map))
+    }
+
+    /// The value of an entry with nothing after its `-` or `key:`: a more
+    /// indented block, or null.
+    fn nested(&mut self, indent: usize) -> Result<Value> {
+        match self.lines.get(self.pos) {
+            Some(next) if next.indent > indent => self.block(next.indent),
+            _ => Ok(Value::Null),
+        }
+    }
+}
+
+/// Splits `key: rest` off a line, or returns `None` if the line is not a mapping entry.
+fn split_key<'a>(line: Line<'a>) -> Result<Option<(String, &'a str)>> {
+    let text = line.text;
+    if text.starts_with('"') || text.starts_with('\'') {
+        let mut inline = Inline::new(line, text);
+        let key = inline.quoted()?;
+        let rest = &text[inline.pos..];
+        return Ok(match rest.strip_prefix(':') {
+            Some(rest) if rest.is_empty() || rest.starts_with(' ') => Some((key, rest.trim_start())),
+            _ => None,
+        });
+    }
+    if text.starts_with('[') || text.starts_with('{') || is_item(text) {
+        return Ok(None);
+    }
+    let end = text.find(": ").or_else(|| text.strip_suffix(':').map(str::len));
+    Ok(end.map(|end| (text[..end].trim_end().to_string(), text[end + 1..].trim_start())))
+}
+
+/// Parser for a scalar or flow collection within one line.
+struct Inline<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    /// Column of the start of `text`.
+    column: usize,
+}
+
+impl<'a> Inline<'a> {
+    fn new(line: Line, text: &'a str) -> Self {
+        let offset = line.text[..line.text.len() - text.len()].chars().count();
+        Inline { text, pos: 0, line: line.number, column: line.indent + offset + 1 }
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        error(self.line, self.column + self.text[..self.pos].chars().count(), message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(format!("expected `{}`", expected))),
+        }
+    }
+
+    fn value_to_end(mut self) -> Result<Value> {
+        let value = self.value(false)?;
+        self.skip_spaces();
+        match self.peek() {
+            None => Ok(value),
+            Some(_) => Err(self.error("unexpected characters after value")),
+        }
+    }
+
+    fn value(&mut self, in_flow: bool) -> Result<Value> {
+        self.skip_spaces();
+        match self.peek() {
+            Some('[') => self.flow_sequence(),
+            Some('{') => self.flow_mapping(),
+            Some('"') | Some('\'') => Ok(Value::String(self.quoted()?)),
+            Some('|') | Some('>') => Err(self.error("block scalars are not supported")),
+            Some('&') | Some('*') | Some('!') => Err(self.error("anchors, aliases and tags are not supported")),
+            _ => Ok(plain_value(self.plain(in_flow))),
+        }
+    }
+
+    fn flow_sequence(&mut self) -> Result<Value> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value(true)?);
+            self.skip_spaces();
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                None => return Err(self.error("flow sequences must end on the same line")),
+                Some(_) => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn flow_mapping(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        let mut map = Map::new();
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(Value::Object(map));
+            }
+            let key = match self.peek() {
+                Some('"') | Some('\'') => self.quoted()?,
+                _ => self.plain(true),
+            };
+            self.skip_spaces();
+            self.expect(':')?;
+            let value = self.value(true)?;
+            if map.insert(key.clone(), value).is_some() {
+                return Err(self.error(format!("duplicate key: {}", key)));
+            }
+            self.skip_spaces();
+            match self.bump() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(map)),
+                None => return Err(self.error("flow mappings must end on the same line")),
+                Some(_) => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    /// A plain scalar: up to `: ` (or `,`, `]`, `}` inside flow collections).
+    fn plain(&mut self, in_flow: bool) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            let rest = &self.text[self.pos + c.len_utf8()..];
+            let ends_key = c == ':' && (rest.is_empty() || rest.starts_with(' ') || (in_flow && rest.starts_with([',', ']', '}'])));
+            if ends_key || (in_flow && matches!(c, ',' | ']' | '}')) {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        self.text[start..self.pos].trim_end().to_string()
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let quote = self.bump().expect("called on a quote");
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some('\'') if quote == '\'' => {
+                    if self.peek() == Some('\'') {
+                        self.pos += 1;
+                        value.push('\'');
+                    } else {
+                        return Ok(value);
+                    }
+                }
+                Some('"') if quote == '"' => return Ok(value),
+                Some('\\') if quote == '"' => value.push(self.escape()?),
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char> {
+        let c = match self.bump() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('0') => '\0',
+            Some(c @ ('"' | '\\' | '/' | ' ')) => c,
+            Some('u') => {
+                let hex = self.text.get(self.pos..self.pos + 4).ok_or_else(|| self.error("invalid \\u escape"))?;
+                let code = u32::from_str_radix(hex, 16).map_err(|_| self.error("invalid \\u escape"))?;
+                self.pos += 4;
+                char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))?
+            }
+            _ => return Err(self.error("unknown escape sequence")),
+        };
+        Ok(c)
+    }
+}
+
+/// Types a plain scalar the way YAML's core schema does. Infinity and NaN
+/// have no JSON equivalent and stay strings.
+fn plain_value(text: String) -> Value {
+    match text.as_str() {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+    let radix = |prefix: &str, radix: u32| {
+        let digits = text.strip_prefix(prefix)?;
+        let valid = !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix));
+        valid.then(|| i64::from_str_radix(digits, radix).ok()).flatten()
+    };
+    if let Some(n) = radix("0x", 16).or_else(|| radix("0o", 8)) {
+        return Value::Number(n.into());
+    }
+    let numeric = text.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c))
+        && text.chars().any(|c| c.is_ascii_digit())
+        && text.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+    if numeric {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::Number(n.into());
+        }
+        if let Some(n) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(text)
+}
+
+/// Writes `value` as YAML: the top `block_depth` levels as block collections
+/// and anything deeper in flow style, mirroring the JSON layout.
+pub(crate) fn to_string(value: &Value, block_depth: usize) -> String {
+    let mut out = String::new();
+    write_block(&mut out, value, block_depth, 0, 0, false);
+    out
+}
+
+fn is_block(value: &Value, block_depth: usize, depth: usize) -> bool {
+    depth < block_depth
+        && match value {
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(map) => !map.is_empty(),
+            _ => false,
+        }
+}
+
+/// Writes a node whose lines are indented by `indent`; with `continued`, the
+/// first line goes on the current line (after a `- `).
+fn write_block(out: &mut String, value: &Value, block_depth: usize, indent: usize, depth: usize, mut continued: bool) {
+    let mut pad = |out: &mut String| {
+        if !continued {
+            out.push_str(&" ".repeat(indent));
+        }
+        continued = false;
+    };
+    match value {
+        Value::Array(items) if is_block(value, block_depth, depth) => {
+            for item in items {
+                pad(out);
+                if is_block(item, block_depth, depth + 1) {
+                    out.push_str("- ");
+                    write_block(out, item, block_depth, indent + 2, depth + 1, true);
+                } else {
+                    out.push_str(&format!("- {}\n", flow(item)));
+                }
+            }
+        }
+        Value::Object(map) if is_block(value, block_depth, depth) => {
+            for (key, item) in ordered(map) {
+                pad(out);
+                if is_block(item, block_depth, depth + 1) {
+                    out.push_str(&format!("{}:\n", scalar(key)));
+                    write_block(out, item, block_depth, indent + 2, depth + 1, false);
+                } else {
+                    out.push_str(&format!("{}: {}\n", scalar(key), flow(item)));
+                }
+            }
+        }
+        _ => {
+            pad(out);
+            out.push_str(&flow(value));
+            out.push('\n');
+        }
+    }
+}
+
+fn flow(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => scalar(s),
+        Value::Array(items) => format!("[{}]", items.iter().map(flow).collect::<Vec<_>>().join(", ")),
+        Value::Object(map) => {
+            let entries: Vec<String> =
+                ordered(map).into_iter().map(|(key, value)| format!("{}: {}", scalar(key), flow(value))).collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+/// A string as a plain scalar when that reads back as the same string,
+/// otherwise double-quoted (JSON string syntax is valid YAML).
+fn scalar(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !s.contains(|c: char| c.is_control() || ",[]{}".contains(c))
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !is_ambiguous(s)
+        && plain_value(s.to_string()) == Value::String(s.to_string());
+    if plain {
+        s.to_string()
+    } else {
+        serde_json::to_string(s).expect("strings always serialize")
+    }
+}
+
+/// Whether other YAML readers might not take plain `s` as a string: the core
+/// schema's infinity and NaN, YAML 1.1 booleans, and number-like text such as
+/// `0b101`, `1_000` or `1:30`.
+fn is_ambiguous(s: &str) -> bool {
+    const BOOLEANS: [&str; 16] =
+        ["y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off", "Off", "OFF"];
+    BOOLEANS.contains(&s)
+        || [".nan", ".NaN", ".NAN"].contains(&s)
+        || [".inf", ".Inf", ".INF"].contains(&s.trim_start_matches(['+', '-']))
+        || (s.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c))
+            && s.chars().any(|c| c.is_ascii_digit())
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || "+-._:".contains(c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip(value: &Value) -> Value {
+        parse(&to_string(value, 3)).unwrap()
+    }
+
+    #[test]
+    fn round_trips_character_data() {
+        let value = json!({
+            "version": 2,
+            "characters": [
+                {
+                    "name": "深淵のユピネ",
+                    "alias": ["Abyssal Yufine", "A.Yufine", "it's", "say \"hi\"", "a: b", "#tag", "- dash", "\" #x", "\\", "it's #x"],
+                    "advantages": ["Mage", {"name": "ヴィオレタ", "weight": 2}],
+                    "disadvantages": [],
+                    "class": "mage",
+                },
+                {"name": "", "alias": [], "advantages": [""], "disadvantages": []},
+            ],
+        });
+        assert_eq!(round_trip(&value), value);
+    }
+
+    #[test]
+    fn quotes_strings_other_readers_would_type() {
+        let ambiguous = [
+            "0x1F", "0o17", "0b11", ".inf", "-.Inf", "+.INF", ".nan", ".NaN", "yes", "No", "on", "OFF", "y", "1_000",
+            "12:30", "1st", "42", "-1.5e3", "true", "null", "~",
+        ];
+        for s in ambiguous {
+            assert!(scalar(s).starts_with('"'), "{} should be quoted", s);
+            assert_eq!(round_trip(&json!([s])), json!([s]));
+        }
+        for s in ["Mage", "A.Yufine", "Abyssal Yufine", "ヴィオレタ", "yesterday", "x1"] {
+            assert_eq!(scalar(s), s);
+        }
+    }
+
+    #[test]
+    fn types_plain_scalars_like_the_core_schema() {
+        let source = "- 0x1F\n- 0o17\n- 12\n- -3\n- 1.5\n- true\n- False\n- ~\n- null\n- .inf\n- 0xZZ\n- text\n";
+        let expected = json!([31, 15, 12, -3, 1.5, true, false, null, null, ".inf", "0xZZ", "text"]);
+        assert_eq!(parse(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn reads_comments_quotes_and_flow_collections() {
+        let source = "---\n# characters\n- name: 'it''s #1' # trailing\n  alias: [\"a#b\", \"\\\" #c\"]\n  advantages: [{name: X, weight: 2}]\n  disadvantages: []\n";
+        let expected = json!([{
+            "name": "it's #1",
+            "alias": ["a#b", "\" #c"],
+            "advantages": [{"name": "X", "weight": 2}],
+            "disadvantages": [],
+        }]);
+        assert_eq!(parse(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_unsupported_syntax_with_a_position() {
+        let cases = [
+            ("- name: &a X\n", 1),
+            ("- name: !!str X\n", 1),
+            ("- name: |\n    X\n", 1),
+            ("- alias: [a,\n    b]\n", 1),
+            ("- name: \"open\n", 1),
+            ("- a\n\t- b\n", 2),
+            ("a: 1\n  b: 2\n", 2),
+        ];
+        for (source, line) in cases {
+            match parse(source) {
+                Err(Error::Parse { line: found, .. }) => assert_eq!(found, line, "{:?}", source),
+                other => panic!("{:?} should not parse: {:?}", source, other),
+            }
+        }
+    }
+}